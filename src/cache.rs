@@ -1,9 +1,11 @@
 use std::{
-    cell::RefCell,
     collections::HashMap,
     io::Read,
     path::{Path, PathBuf},
-    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use jiff::{Span, Timestamp, ToSpan, Unit};
@@ -15,18 +17,18 @@ use crate::{based_path::BasedPath, error::Error};
 struct Inner {
     fs: RelFs,
     base: BasedPath,
-    cache: RefCell<HashMap<String, Vec<u8>>>,
+    cache: Mutex<HashMap<String, Vec<u8>>>,
 }
 
 #[derive(Clone)]
 pub struct Cache {
-    inner: Rc<Inner>,
+    inner: Arc<Inner>,
 }
 
 impl Cache {
     pub fn new(dir: BasedPath) -> Self {
         Self {
-            inner: Rc::new(Inner {
+            inner: Arc::new(Inner {
                 fs: RelFs::new(&dir),
                 base: dir,
                 cache: Default::default(),
@@ -54,14 +56,28 @@ impl Cache {
         std::fs::create_dir_all(&path).map_err(|e| logix_vfs::Error::from_io(path.to_path_buf(), e))
     }
 
+    /// Write to a temporary file that replaces `path` when complete, so concurrent
+    /// readers never see a partially written file
     fn write_file(&self, path: &Path, data: &[u8]) -> Result<(), logix_vfs::Error> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
         let path = self
             .inner
             .base
             .join(self.inner.fs.canonicalize_path(path)?)
             .unwrap();
+        let tmp_path = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
         // TODO: Add this functionality to RelFs itself?
-        std::fs::write(&path, data).map_err(|e| logix_vfs::Error::from_io(path.to_path_buf(), e))
+        std::fs::write(&tmp_path, data)
+            .and_then(|()| std::fs::rename(&tmp_path, &path))
+            .map_err(|e| {
+                std::fs::remove_file(&tmp_path).ok();
+                logix_vfs::Error::from_io(path.to_path_buf(), e)
+            })
     }
 
     fn modified_time(&self, path: &Path) -> Result<Timestamp, logix_vfs::Error> {
@@ -83,7 +99,7 @@ impl Cache {
         ttl: Span,
         f: impl FnOnce() -> Result<T, Error>,
     ) -> Result<T, Error> {
-        if let Some(ret) = self.inner.cache.borrow().get(key) {
+        if let Some(ret) = self.inner.cache.lock().unwrap().get(key) {
             log::debug!("Found {key:?} in memory");
             // NOTE: This shouldn't ever fail as we just serialized it within the same process
             return Ok(serde_json::from_slice(ret).unwrap());
@@ -105,7 +121,7 @@ impl Cache {
                 match self.read_file(&path) {
                     Ok(data) => match serde_json::from_slice(&data) {
                         Ok(ret) => {
-                            self.inner.cache.borrow_mut().insert(key.into(), data);
+                            self.inner.cache.lock().unwrap().insert(key.into(), data);
                             return Ok(ret);
                         }
                         Err(e) => {
@@ -127,7 +143,7 @@ impl Cache {
             Ok(_) => match serde_json::to_vec_pretty(&ret) {
                 Ok(data) => match self.write_file(&path, &data) {
                     Ok(_) => {
-                        self.inner.cache.borrow_mut().insert(key.into(), data);
+                        self.inner.cache.lock().unwrap().insert(key.into(), data);
                     }
                     Err(e) => {
                         log::warn!("Failed to store cached item to {path:?}: {e}");
@@ -24,7 +24,7 @@ pub enum Ssh {
     OpenSSH { agent: SshAgent, keys: Map<String> },
 }
 
-#[derive(Debug, LogixType)]
+#[derive(Debug, LogixType, PartialEq, Eq)]
 pub enum Command {
     ShellCommand { command: String },
 }
@@ -58,6 +58,10 @@ pub enum ConfigDir {
     User {
         package_name: Option<ShortStr>,
         filter: Option<Filter>,
+        /// Used to decide the owner when several owners claim the same file, the
+        /// highest priority wins. Built-in owners such as ssh use priority 0,
+        /// which is also the default
+        priority: Option<i32>,
     },
 }
 
@@ -81,9 +85,148 @@ pub enum RustCrateSource {
         repo: String,
         revision: String,
     },
+
+    /// A branch of a project on GitLab, `base_url` defaults to `https://gitlab.com`
+    GitLabBranch {
+        base_url: Option<String>,
+        project: String,
+        branch: String,
+    },
+
+    /// A branch of a repository on a Gitea or Forgejo instance
+    GiteaBranch {
+        base_url: String,
+        owner: String,
+        repo: String,
+        branch: String,
+    },
+
+    /// A branch of a repository on Codeberg
+    CodebergBranch {
+        owner: String,
+        repo: String,
+        branch: String,
+    },
+
+    /// A branch of any git repository, `branch` defaults to the default branch
+    GitBranch { url: String, branch: Option<String> },
+}
+
+impl RustCrateSource {
+    /// The url passed to `cargo install --git`
+    pub fn git_url(&self) -> String {
+        match self {
+            Self::GithubBranch { owner, repo, .. }
+            | Self::GithubTag { owner, repo, .. }
+            | Self::GithubRevision { owner, repo, .. } => {
+                format!("https://github.com/{owner}/{repo}.git")
+            }
+            Self::GitLabBranch {
+                base_url,
+                project,
+                branch: _,
+            } => format!(
+                "{}/{project}.git",
+                base_url
+                    .as_deref()
+                    .unwrap_or(GITLAB_URL)
+                    .trim_end_matches('/')
+            ),
+            Self::GiteaBranch {
+                base_url,
+                owner,
+                repo,
+                branch: _,
+            } => format!("{}/{owner}/{repo}.git", base_url.trim_end_matches('/')),
+            Self::CodebergBranch {
+                owner,
+                repo,
+                branch: _,
+            } => format!("{CODEBERG_URL}/{owner}/{repo}.git"),
+            Self::GitBranch { url, branch: _ } => url.clone(),
+        }
+    }
+
+    /// Describes the source as the repository and the branch, tag or revision
+    pub fn describe(&self) -> String {
+        let url = self.git_url();
+        match self {
+            Self::GithubBranch { branch, .. }
+            | Self::GitLabBranch { branch, .. }
+            | Self::GiteaBranch { branch, .. }
+            | Self::CodebergBranch { branch, .. } => format!("{url} branch {branch}"),
+            Self::GithubTag { tag, .. } => format!("{url} tag {tag}"),
+            Self::GithubRevision { revision, .. } => format!("{url} revision {revision}"),
+            Self::GitBranch {
+                url: _,
+                branch: Some(branch),
+            } => format!("{url} branch {branch}"),
+            Self::GitBranch {
+                url: _,
+                branch: None,
+            } => url,
+        }
+    }
+}
+
+/// Flags passed to `cargo install`
+#[derive(Debug, LogixType, PartialEq, Eq, Clone, Copy)]
+pub enum CargoFlag {
+    /// Use the `Cargo.lock` of the crate, passed as `--locked`
+    Locked,
+    /// Do not enable the default features, passed as `--no-default-features`
+    NoDefaultFeatures,
+    /// Enable all the features, passed as `--all-features`
+    AllFeatures,
+}
+
+/// The tool used to install a [Package::RustCrate]
+#[derive(Debug, LogixType, PartialEq, Eq, Clone, Copy)]
+pub enum CargoInstaller {
+    /// Build the crate from source using `cargo install`
+    Cargo,
+    /// Download prebuilt binaries using `cargo binstall --no-confirm`, falls back to
+    /// `cargo install` if it is not available or the crate needs to be built from source
+    Binstall,
+}
+
+/// Options used when installing a [Package::RustCrate]
+#[derive(Debug, LogixType, PartialEq, Eq)]
+pub struct CargoInstallOptions {
+    /// The tool used to install the crate, overrides [PackageDefaults::cargo_installer]
+    pub installer: Option<CargoInstaller>,
+    /// Features to enable, passed as `--features`
+    pub features: Option<Vec<ShortStr>>,
+    /// Additional flags such as `Locked`
+    pub flags: Option<Vec<CargoFlag>>,
+    /// Only install the specified binaries, passed as `--bin`
+    pub bins: Option<Vec<ShortStr>>,
+    /// The profile to build with, passed as `--profile`
+    pub profile: Option<ShortStr>,
+    /// The toolchain to use, such as `"nightly"` which is passed as `+nightly`
+    pub toolchain: Option<ShortStr>,
+}
+
+impl CargoInstallOptions {
+    pub const EMPTY: &'static CargoInstallOptions = &CargoInstallOptions {
+        installer: None,
+        features: None,
+        flags: None,
+        bins: None,
+        profile: None,
+        toolchain: None,
+    };
+
+    /// Check if the specified flag is set
+    pub fn has_flag(&self, flag: CargoFlag) -> bool {
+        self.flags
+            .as_ref()
+            .is_some_and(|flags| flags.contains(&flag))
+    }
 }
 
 /// A package of various types that is managed by logix
+#[allow(clippy::large_enum_variant)] // NOTE: The config is only loaded once
 #[derive(Debug, LogixType, PartialEq, Eq)]
 pub enum Package {
     /// The package is a rust crate installed using cargo
@@ -99,6 +242,14 @@ pub enum Package {
 
         /// Environment variables that will be set
         environment: Map<String>,
+
+        /// Options passed to `cargo install`
+        install_options: Option<CargoInstallOptions>,
+
+        /// Restrict the versions that may be installed using a semver requirement
+        /// such as `">=0.10, <0.12"` or an exact pin such as `"=0.11.2"`. Only
+        /// applies to crates from crates.io
+        version: Option<ShortStr>,
     },
     /// A custom package which will be downloaded and built from source. It
     /// will also be kept up to date by logix.
@@ -108,16 +259,231 @@ pub enum Package {
         /// Where to store the downloaded sources, defaults to a predictable directory
         /// under `./cache/logix` or similar (depending on platform)
         local_dir: Option<ValidPath>,
-        // TODO: install: Command,
+        /// Command used to build the package, it is run in the local directory
+        build: Option<Command>,
+        /// Command used to install the package after it is built, it is run in the local directory
+        install: Option<Command>,
+        /// Specified the config files that needs to be managed by this package
+        config_dir: Option<ConfigDir>,
+    },
+    /// A prebuilt binary downloaded from the releases of a GitHub repository
+    GitHubRelease {
+        owner: ShortStr,
+        repo: ShortStr,
+        /// Regular expression matching the name of the asset to download, `{os}`
+        /// and `{arch}` are replaced by the names commonly used for the host. By
+        /// default an archive mentioning both the host OS and architecture is used
+        asset_pattern: Option<ShortStr>,
+        /// Name of the binary to install to `~/.local/bin`, defaults to the package name
+        bin: Option<ShortStr>,
+        /// Name of the asset containing the SHA256 checksum of the downloaded asset,
+        /// `{asset}` is replaced by the name of the downloaded asset. By default an
+        /// asset such as `{asset}.sha256`, `SHA256SUMS` or `checksums.txt` is used,
+        /// and the download is rejected if none is found
+        checksum_asset: Option<ShortStr>,
+        /// Restrict the versions that may be installed using a semver requirement
+        version: Option<ShortStr>,
+        /// Specified the config files that needs to be managed by this package
+        config_dir: Option<ConfigDir>,
+    },
+    /// A versioned archive such as a `.tar.gz` or `.zip` downloaded from an url
+    Archive {
+        /// The url of the archive, `{version}` is replaced by the version
+        url_template: String,
+        /// The version to install, must be a semver version
+        version: ShortStr,
+        /// The SHA256 checksum of the archive, the download is rejected if it differs
+        sha256: ShortStr,
+        /// Number of leading path components to remove when extracting, such as a
+        /// top level directory named after the version
+        strip_components: Option<u32>,
+        /// Paths of binaries in the extracted archive to link into `~/.local/bin`
+        bins: Option<Vec<ShortStr>>,
+        /// Specified the config files that needs to be managed by this package
+        config_dir: Option<ConfigDir>,
+    },
+    /// A python application from PyPI installed into its own environment
+    PythonTool {
+        /// Name of the package on PyPI, defaults to the package name
+        package_name: Option<ShortStr>,
+        /// The tool used to install the package, defaults to [PythonInstaller::Pipx]
+        installer: Option<PythonInstaller>,
+        /// Restrict the versions that may be installed using a semver requirement
+        version: Option<ShortStr>,
+        /// Specified the config files that needs to be managed by this package
+        config_dir: Option<ConfigDir>,
+    },
+    /// A node package installed using `npm install -g --prefix ~/.local`
+    NpmPackage {
+        /// Name of the package on npm such as `@scope/name`, defaults to the package name
+        package_name: Option<ShortStr>,
+        /// Restrict the versions that may be installed using a semver requirement
+        version: Option<ShortStr>,
+        /// Specified the config files that needs to be managed by this package
+        config_dir: Option<ConfigDir>,
+    },
+    /// A package from the distribution installed using its package manager, which
+    /// is detected using `/etc/os-release`. The package is skipped on distributions
+    /// where it has no name
+    System {
+        /// Name of the package on distributions using apt such as Debian and Ubuntu
+        apt: Option<ShortStr>,
+        /// Name of the package on distributions using dnf such as Fedora
+        dnf: Option<ShortStr>,
+        /// Name of the package on distributions using pacman such as Arch Linux
+        pacman: Option<ShortStr>,
+        /// What to do if the package is missing, defaults to [SystemPackageMode::Check]
+        mode: Option<SystemPackageMode>,
+        /// Specified the config files that needs to be managed by this package
+        config_dir: Option<ConfigDir>,
+    },
+    /// A go program installed using `go install`
+    GoPackage {
+        /// The package to install such as `golang.org/x/tools/gopls`
+        package_path: ShortStr,
+        /// The module containing the package, defaults to `package_path`
+        module: Option<ShortStr>,
+        /// Restrict the versions that may be installed using a semver requirement
+        version: Option<ShortStr>,
+        /// Specified the config files that needs to be managed by this package
+        config_dir: Option<ConfigDir>,
+    },
+    /// A package installed by an external provider executable such as
+    /// `logix-provider-foo`, which logix talks to using JSON over stdin and stdout
+    Plugin {
+        /// Name of the provider executable, looked up in `PATH` unless it is a path
+        provider: ShortStr,
+        /// Arguments passed as is to the provider
+        args: Map<String>,
+        /// Specified the config files that needs to be managed by this package
+        config_dir: Option<ConfigDir>,
+    },
+    /// A new kind of package installed by a provider that a library user registered
+    /// using `SystemState::register_provider`, see [Package::is_extension]
+    Extension {
+        /// The name of the kind of package, which the registered provider handles
+        provider: ShortStr,
+        /// Arguments passed as is to the provider
+        args: Map<String>,
         /// Specified the config files that needs to be managed by this package
         config_dir: Option<ConfigDir>,
     },
 }
 
+impl Package {
+    /// Check if the package is a [Package::Extension] handled by the provider `name`
+    pub fn is_extension(&self, name: &str) -> bool {
+        matches!(self, Self::Extension { provider, .. } if **provider == *name)
+    }
+}
+
+/// The tool used to install a [Package::PythonTool]
+#[derive(Debug, LogixType, PartialEq, Eq, Clone, Copy)]
+pub enum PythonInstaller {
+    /// Install using `pipx install`
+    Pipx,
+    /// Install using `uv tool install`
+    Uv,
+}
+
+/// How logix handles a missing or outdated [Package::System]
+#[derive(Debug, LogixType, PartialEq, Eq, Clone, Copy)]
+pub enum SystemPackageMode {
+    /// Only report that the package needs to be installed
+    Check,
+    /// Install the package using `sudo` and the package manager
+    Install,
+}
+
 /// Points to the source of a [Package] and may be used to look for new versions
 #[derive(Debug, LogixType, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Source {
-    GitHub { owner: ShortStr, repo: ShortStr },
+    GitHub {
+        owner: ShortStr,
+        repo: ShortStr,
+    },
+    /// A project on GitLab, `project` is the full path such as `group/subgroup/repo`
+    /// and `base_url` defaults to `https://gitlab.com`
+    GitLab {
+        base_url: Option<ShortStr>,
+        project: ShortStr,
+    },
+    /// A repository on a Gitea or Forgejo instance
+    Gitea {
+        base_url: ShortStr,
+        owner: ShortStr,
+        repo: ShortStr,
+    },
+    /// A repository on Codeberg, the same as [Source::Gitea] using `https://codeberg.org`
+    Codeberg {
+        owner: ShortStr,
+        repo: ShortStr,
+    },
+    /// Any git repository, the latest commit of `branch` is looked up using `git ls-remote`
+    Git {
+        url: String,
+        branch: Option<ShortStr>,
+    },
+}
+
+/// The base url of gitlab.com
+pub const GITLAB_URL: &str = "https://gitlab.com";
+
+/// The base url of codeberg.org
+pub const CODEBERG_URL: &str = "https://codeberg.org";
+
+impl Source {
+    /// The url used to clone the source
+    pub fn git_url(&self) -> String {
+        match self {
+            Self::GitHub { owner, repo } => format!("https://github.com/{owner}/{repo}.git"),
+            Self::GitLab { base_url, project } => format!(
+                "{}/{project}.git",
+                base_url
+                    .as_deref()
+                    .unwrap_or(GITLAB_URL)
+                    .trim_end_matches('/')
+            ),
+            Self::Gitea {
+                base_url,
+                owner,
+                repo,
+            } => format!("{}/{owner}/{repo}.git", base_url.trim_end_matches('/')),
+            Self::Codeberg { owner, repo } => format!("{CODEBERG_URL}/{owner}/{repo}.git"),
+            Self::Git { url, branch: _ } => url.clone(),
+        }
+    }
+
+    /// Describes the source as the repository and the branch, if it is not the default branch
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Git {
+                url,
+                branch: Some(branch),
+            } => format!("{url} branch {branch}"),
+            Self::GitHub { .. }
+            | Self::GitLab { .. }
+            | Self::Gitea { .. }
+            | Self::Codeberg { .. }
+            | Self::Git { .. } => self.git_url(),
+        }
+    }
+}
+
+/// Settings that apply to all packages unless overridden by the package
+#[derive(Debug, LogixType, PartialEq, Eq)]
+pub struct PackageDefaults {
+    /// The tool used to install rust crates, defaults to [CargoInstaller::Cargo]
+    pub cargo_installer: Option<CargoInstaller>,
+    /// The tool used to install python tools, defaults to [PythonInstaller::Pipx]
+    pub python_installer: Option<PythonInstaller>,
+}
+
+impl PackageDefaults {
+    pub const EMPTY: &'static PackageDefaults = &PackageDefaults {
+        cargo_installer: None,
+        python_installer: None,
+    };
 }
 
 #[derive(Debug, LogixType)]
@@ -132,6 +498,8 @@ pub struct UserProfile {
     pub ssh: Option<Ssh>,
     /// Packages installed to the users home directory, such as to `local/.bin`
     pub packages: Map<Package, Arc<str>>,
+    /// Settings that apply to all the packages
+    pub package_defaults: Option<PackageDefaults>,
 }
 
 /// The root of the logix config
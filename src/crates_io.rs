@@ -0,0 +1,45 @@
+use jiff::ToSpan;
+use serde::{Deserialize, Serialize};
+
+use crate::{cache::Cache, error::Error, url_fetch::UrlFetch};
+
+#[derive(Serialize, Deserialize)]
+pub struct CratesIoVersions {
+    pub versions: Vec<CratesIoVersion>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CratesIoVersion {
+    pub num: semver::Version,
+    pub yanked: bool,
+}
+
+pub struct CratesIoCrate {
+    url: String,
+    key: String,
+    cache: Cache,
+}
+
+impl CratesIoCrate {
+    pub fn new(name: &str, cache: &Cache) -> Self {
+        Self {
+            url: format!("https://crates.io/api/v1/crates/{name}/versions"),
+            key: format!("crates-io/{name}/versions"),
+            cache: cache.clone(),
+        }
+    }
+
+    /// Get all the published versions of the crate that are not yanked
+    pub fn get_versions(&self) -> Result<Vec<semver::Version>, Error> {
+        let res: CratesIoVersions = self.cache.get_or_insert(&self.key, 1.hour(), || {
+            UrlFetch::new(&self.url)?.get()?.json()
+        })?;
+
+        Ok(res
+            .versions
+            .into_iter()
+            .filter(|v| !v.yanked)
+            .map(|v| v.num)
+            .collect())
+    }
+}
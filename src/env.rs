@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use logix_type::types::FullPath;
 
@@ -40,6 +40,9 @@ impl ShadowedDir {
 /// Builder used to specify the environment to use when initializing [Env], created by calling [Env::builder]
 pub struct EnvBuilder {
     home_dir: Option<FullPath>,
+    cargo_home: Option<FullPath>,
+    go_bin: Option<FullPath>,
+    os_release: Option<FullPath>,
 }
 
 impl EnvBuilder {
@@ -52,6 +55,29 @@ impl EnvBuilder {
             );
         }
 
+        if self.cargo_home.is_none() {
+            self.cargo_home = Some(
+                FullPath::try_from(home::cargo_home().map_err(|_| Error::LocateDir("cargo home"))?)
+                    .map_err(|e| Error::InvalidDir("cargo home", e))?,
+            );
+        }
+
+        if self.go_bin.is_none() {
+            // NOTE: Falls back to `~/go/bin` when building the env, same as `go install`
+            let go_bin = std::env::var_os("GOBIN")
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| {
+                    std::env::var_os("GOPATH")
+                        .filter(|v| !v.is_empty())
+                        .map(|v| PathBuf::from(v).join("bin"))
+                });
+            if let Some(go_bin) = go_bin {
+                self.go_bin =
+                    Some(FullPath::try_from(go_bin).map_err(|e| Error::InvalidDir("go bin", e))?);
+            }
+        }
+
         Ok(self)
     }
 
@@ -72,6 +98,25 @@ impl EnvBuilder {
 
         let user_cache = user_dir.join(".cache")?;
 
+        let user_state = user_dir.join(".local/state")?;
+
+        let user_bin = user_dir.join(".local/bin")?;
+
+        let cargo_home = match self.cargo_home.take() {
+            Some(path) => BasedPath::new(path),
+            None => user_dir.join(".cargo")?,
+        };
+
+        let go_bin = match self.go_bin.take() {
+            Some(path) => BasedPath::new(path),
+            None => user_dir.join("go/bin")?,
+        };
+
+        let os_release = self
+            .os_release
+            .take()
+            .map_or_else(|| PathBuf::from("/etc/os-release"), |v| v.to_path_buf());
+
         Ok(Env {
             user_config: ShadowedDir {
                 local: user_config_dir.clone(),
@@ -85,6 +130,16 @@ impl EnvBuilder {
             logix_root,
 
             user_cache,
+
+            user_state,
+
+            user_bin,
+
+            cargo_home,
+
+            go_bin,
+
+            os_release,
         })
     }
 
@@ -92,6 +147,24 @@ impl EnvBuilder {
         self.home_dir = Some(path);
         self
     }
+
+    /// Set the cargo home directory, defaults to `~/.cargo`
+    pub fn cargo_home(&mut self, path: FullPath) -> &mut Self {
+        self.cargo_home = Some(path);
+        self
+    }
+
+    /// Set the directory `go install` installs into, defaults to `~/go/bin`
+    pub fn go_bin(&mut self, path: FullPath) -> &mut Self {
+        self.go_bin = Some(path);
+        self
+    }
+
+    /// Set the file used to detect the distribution, defaults to `/etc/os-release`
+    pub fn os_release(&mut self, path: FullPath) -> &mut Self {
+        self.os_release = Some(path);
+        self
+    }
 }
 
 /// Contains a pre-calculated version of the environment such as various directories.
@@ -106,11 +179,31 @@ pub struct Env {
 
     /// ~/.cache
     user_cache: BasedPath,
+
+    /// ~/.local/state
+    user_state: BasedPath,
+
+    /// ~/.local/bin
+    user_bin: BasedPath,
+
+    /// ~/.cargo or $CARGO_HOME
+    cargo_home: BasedPath,
+
+    /// ~/go/bin, $GOBIN or $GOPATH/bin
+    go_bin: BasedPath,
+
+    /// /etc/os-release
+    os_release: PathBuf,
 }
 
 impl Env {
     pub fn builder() -> EnvBuilder {
-        EnvBuilder { home_dir: None }
+        EnvBuilder {
+            home_dir: None,
+            cargo_home: None,
+            go_bin: None,
+            os_release: None,
+        }
     }
 
     /// Create an [Env] instance using the OS environment
@@ -139,4 +232,29 @@ impl Env {
     pub fn user_cache(&self) -> &BasedPath {
         &self.user_cache
     }
+
+    /// Returns the state directory of the current user such as `~/.local/state`
+    pub fn user_state(&self) -> &BasedPath {
+        &self.user_state
+    }
+
+    /// Returns the directory where binaries of the current user are installed such as `~/.local/bin`
+    pub fn user_bin(&self) -> &BasedPath {
+        &self.user_bin
+    }
+
+    /// Returns the cargo home directory such as `~/.cargo`
+    pub fn cargo_home(&self) -> &BasedPath {
+        &self.cargo_home
+    }
+
+    /// Returns the directory where `go install` installs binaries such as `~/go/bin`
+    pub fn go_bin(&self) -> &BasedPath {
+        &self.go_bin
+    }
+
+    /// Returns the file describing the distribution such as `/etc/os-release`
+    pub fn os_release(&self) -> &Path {
+        &self.os_release
+    }
 }
@@ -5,7 +5,7 @@ use logix_type::{
     types::FullPath,
 };
 
-use crate::based_path::BasedPath;
+use crate::{based_path::BasedPath, managed_file::Owner};
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
@@ -24,9 +24,6 @@ pub enum Error {
     #[error("{0:?}")]
     ParseError(#[from] ParseError),
 
-    #[error("Failed to walk path: {0}")]
-    WalkDir(walkdir::Error),
-
     #[error("An unexpected error occured when stripping a prefix of a path previously joined with said prefix")]
     StripPrefixFailed,
 
@@ -66,9 +63,93 @@ pub enum Error {
     #[error("Failed to run command {0:?}: {1}")]
     ShellCommandFailed(&'static str, String),
 
+    #[error("Failed to read installed crates from {0:?}: {1}")]
+    ReadCargoState(PathBuf, String),
+
+    #[error("Invalid version requirement {1:?} for package {0:?}: {2}")]
+    InvalidVersionReq(String, String, String),
+
+    #[error("Failed to read lockfile {0:?}: {1}")]
+    ReadLockfile(BasedPath, String),
+
+    #[error("Failed to write lockfile {0:?}: {1}")]
+    WriteLockfile(BasedPath, String),
+
+    #[error("Failed to read the installed packages from {0:?}: {1}")]
+    ReadInstalledState(BasedPath, String),
+
+    #[error("Failed to write the installed packages to {0:?}: {1}")]
+    WriteInstalledState(BasedPath, String),
+
+    #[error("The {1} step of package {0:?} failed: {2}")]
+    CustomPackageStep(String, &'static str, String),
+
+    #[error("Invalid asset pattern {1:?} for package {0:?}: {2}")]
+    InvalidAssetPattern(String, String, String),
+
+    #[error("No release with version {1} found for package {0:?}")]
+    ReleaseNotFound(String, String),
+
+    #[error("No asset matching the host was found for package {0:?} in release {1:?}, set `asset_pattern` to select it")]
+    NoMatchingAsset(String, String),
+
+    #[error("Failed to find the checksum of {0:?} in {1:?}")]
+    ChecksumNotFound(String, String),
+
+    #[error("No checksum was found for {1:?} of package {0:?}, set `checksum_asset` to select it")]
+    NoChecksumAsset(String, String),
+
+    #[error("The checksum of {0:?} does not match, expected {1} but got {2}")]
+    ChecksumMismatch(String, String, String),
+
+    #[error("Failed to extract {0:?}: {1}")]
+    ExtractArchive(String, String),
+
+    #[error("The file {1:?} was not found in {0:?}")]
+    FileNotInArchive(String, String),
+
+    #[error("Failed to install binary {0:?}: {1}")]
+    InstallBinary(PathBuf, String),
+
+    #[error("Invalid version {1:?} for package {0:?}: {2}")]
+    InvalidVersion(String, String, String),
+
+    #[error("Failed to install archive to {0:?}: {1}")]
+    InstallArchive(PathBuf, String),
+
+    #[error("Failed to read the installed npm package {0:?}: {1}")]
+    ReadNpmPackage(PathBuf, String),
+
+    #[error("No supported package manager was detected, apt, dnf and pacman are supported")]
+    NoSystemPackageManager,
+
+    #[error("The package {0:?} is check-only, set `mode: Install` to let logix install it")]
+    SystemPackageCheckOnly(String),
+
+    #[error("No package provider handles {0}")]
+    NoPackageProvider(String),
+
+    #[error("Package {0:?} is not in the config")]
+    UnknownPackage(String),
+
+    #[error("The package {0:?} is not supported by the {1} provider")]
+    UnsupportedPackage(String, &'static str),
+
+    #[error("The custom package built in {0:?} can't be uninstalled automatically, remove the installed files and the directory to forget it")]
+    CustomPackageUninstall(PathBuf),
+
+    #[error("The package provider {0:?} failed: {1}")]
+    PluginFailed(String, String),
+
+    #[error("The reference {1:?} was not found in the git repository {0:?}")]
+    GitRefNotFound(String, String),
+
     #[error("Curl failed: {0}")]
     CurlError(curl::Error),
 
+    #[error("The file {0:?} is claimed by both {1} and {2}, set a higher `priority` on one of them to resolve the conflict")]
+    ConflictingOwners(BasedPath, Owner, Owner),
+
     #[error("Failed to read cached entry {0:?} for file {1:?}: {2}")]
     ReadForCache(String, BasedPath, String),
 }
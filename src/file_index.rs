@@ -0,0 +1,192 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::based_path::BasedPath;
+
+/// Bump this when the format of the index changes, old indexes will then be discarded
+const INDEX_VERSION: u32 = 1;
+
+/// Files modified more recently than this are not stored in the index, as a
+/// new modification within the resolution of the file system timestamps
+/// would otherwise go unnoticed
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// The SHA256 hash of the content of a file
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ContentHash(String);
+
+impl ContentHash {
+    pub fn of_bytes(data: &[u8]) -> Self {
+        Self(format!("{:x}", Sha256::digest(data)))
+    }
+
+    /// The hash as a lower case hex string
+    pub fn as_hex(&self) -> &str {
+        &self.0
+    }
+
+    fn of_file(path: &Path) -> std::io::Result<Self> {
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+        Ok(Self(format!("{:x}", hasher.finalize())))
+    }
+}
+
+/// The metadata used to decide if a file has changed since it was last hashed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct FileMeta {
+    mtime_ns: u128,
+    size: u64,
+    inode: u64,
+}
+
+impl FileMeta {
+    fn from_metadata(meta: &std::fs::Metadata) -> std::io::Result<(Self, SystemTime)> {
+        let mtime = meta.modified()?;
+
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(meta);
+        #[cfg(not(unix))]
+        let inode = 0;
+
+        Ok((
+            Self {
+                mtime_ns: mtime
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos(),
+                size: meta.len(),
+                inode,
+            },
+            mtime,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    meta: FileMeta,
+    hash: ContentHash,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexData {
+    version: u32,
+    entries: HashMap<PathBuf, Entry>,
+}
+
+/// A persistent index of content hashes keyed by path, this makes it possible
+/// to skip reading files whose metadata has not changed since the last run
+pub struct FileIndex {
+    path: Option<BasedPath>,
+    old: HashMap<PathBuf, Entry>,
+    new: Mutex<HashMap<PathBuf, Entry>>,
+}
+
+impl FileIndex {
+    /// Create an index that is not backed by a file, all files will be hashed
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            old: HashMap::new(),
+            new: Default::default(),
+        }
+    }
+
+    /// Load the index stored at `path`, a missing or invalid index is treated as empty
+    pub fn load(path: BasedPath) -> Self {
+        let old = match std::fs::read(&path) {
+            Ok(data) => match serde_json::from_slice::<IndexData>(&data) {
+                Ok(data) if data.version == INDEX_VERSION => data.entries,
+                Ok(data) => {
+                    log::debug!(
+                        "Discarding file index {path:?} with version {} (want {INDEX_VERSION})",
+                        data.version
+                    );
+                    HashMap::new()
+                }
+                Err(e) => {
+                    log::warn!("Failed to parse file index {path:?}: {e}");
+                    HashMap::new()
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                log::warn!("Failed to read file index {path:?}: {e}");
+                HashMap::new()
+            }
+        };
+
+        Self {
+            path: Some(path),
+            old,
+            new: Default::default(),
+        }
+    }
+
+    /// Calculate the hash of the file at `path`, the file is only read if
+    /// the metadata differs from the one stored in the index
+    pub fn hash_file(&self, path: &Path) -> std::io::Result<ContentHash> {
+        let (meta, mtime) = FileMeta::from_metadata(&std::fs::metadata(path)?)?;
+
+        if let Some(entry) = self.old.get(path).filter(|entry| entry.meta == meta) {
+            log::trace!("Using indexed hash for {path:?}");
+            self.new.lock().unwrap().insert(path.into(), entry.clone());
+            return Ok(entry.hash.clone());
+        }
+
+        log::trace!("Hashing {path:?}");
+
+        let hash = ContentHash::of_file(path)?;
+
+        let is_racy = SystemTime::now()
+            .duration_since(mtime)
+            .map_or(true, |age| age < RACY_WINDOW);
+
+        if !is_racy {
+            self.new.lock().unwrap().insert(
+                path.into(),
+                Entry {
+                    meta,
+                    hash: hash.clone(),
+                },
+            );
+        }
+
+        Ok(hash)
+    }
+
+    /// Store the index, only the files hashed since it was loaded are kept
+    pub fn save(self) {
+        let Some(path) = self.path else {
+            return;
+        };
+
+        let data = IndexData {
+            version: INDEX_VERSION,
+            entries: self.new.into_inner().unwrap(),
+        };
+
+        let tmp_path = path.with_extension("json.tmp");
+
+        let res = std::fs::create_dir_all(path.parent().unwrap())
+            .and_then(|()| {
+                std::fs::write(
+                    &tmp_path,
+                    serde_json::to_vec(&data).map_err(std::io::Error::other)?,
+                )
+            })
+            .and_then(|()| std::fs::rename(&tmp_path, &path));
+
+        if let Err(e) = res {
+            log::warn!("Failed to store file index {path:?}: {e}");
+        }
+    }
+}
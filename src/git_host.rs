@@ -0,0 +1,127 @@
+use crate::{
+    cache::Cache,
+    config::{RustCrateSource, Source, CODEBERG_URL, GITLAB_URL},
+    error::Error,
+    git_remote::GitRemote,
+    gitea::GiteaRepo,
+    github::GitHubRepo,
+    gitlab::GitLabProject,
+    managed_package::PackageVersion,
+};
+
+/// A git repository on one of the supported hosts, used to look up commits
+pub enum GitHost {
+    GitHub(GitHubRepo),
+    GitLab(GitLabProject),
+    Gitea(GiteaRepo),
+    /// Not a known host, only `git ls-remote` is available
+    Remote(GitRemote),
+}
+
+impl GitHost {
+    pub fn from_source(source: &Source, cache: &Cache) -> Self {
+        match source {
+            Source::GitHub { owner, repo } => Self::GitHub(GitHubRepo::new(owner, repo, cache)),
+            Source::GitLab { base_url, project } => Self::GitLab(GitLabProject::new(
+                base_url.as_deref().unwrap_or(GITLAB_URL),
+                project,
+                cache,
+            )),
+            Source::Gitea {
+                base_url,
+                owner,
+                repo,
+            } => Self::Gitea(GiteaRepo::new(base_url, owner, repo, cache)),
+            Source::Codeberg { owner, repo } => {
+                Self::Gitea(GiteaRepo::new(CODEBERG_URL, owner, repo, cache))
+            }
+            Source::Git { url, branch: _ } => Self::Remote(GitRemote::new(url, cache)),
+        }
+    }
+
+    /// Get the host and branch of a crate source, `None` for the GitHub sources
+    /// as they also support tags and revisions and are handled separately
+    pub fn from_crate_source<'a>(
+        source: &'a RustCrateSource,
+        cache: &Cache,
+    ) -> Option<(Self, Option<&'a str>)> {
+        match source {
+            RustCrateSource::GithubBranch { .. }
+            | RustCrateSource::GithubTag { .. }
+            | RustCrateSource::GithubRevision { .. } => None,
+            RustCrateSource::GitLabBranch {
+                base_url,
+                project,
+                branch,
+            } => Some((
+                Self::GitLab(GitLabProject::new(
+                    base_url.as_deref().unwrap_or(GITLAB_URL),
+                    project,
+                    cache,
+                )),
+                Some(branch),
+            )),
+            RustCrateSource::GiteaBranch {
+                base_url,
+                owner,
+                repo,
+                branch,
+            } => Some((
+                Self::Gitea(GiteaRepo::new(base_url, owner, repo, cache)),
+                Some(branch),
+            )),
+            RustCrateSource::CodebergBranch {
+                owner,
+                repo,
+                branch,
+            } => Some((
+                Self::Gitea(GiteaRepo::new(CODEBERG_URL, owner, repo, cache)),
+                Some(branch),
+            )),
+            RustCrateSource::GitBranch { url, branch } => {
+                Some((Self::Remote(GitRemote::new(url, cache)), branch.as_deref()))
+            }
+        }
+    }
+
+    pub fn default_branch(&self) -> Result<String, Error> {
+        match self {
+            Self::GitHub(repo) => Ok(repo.get_info()?.default_branch),
+            Self::GitLab(project) => Ok(project.get_info()?.default_branch),
+            Self::Gitea(repo) => Ok(repo.get_info()?.default_branch),
+            Self::Remote(remote) => remote.default_branch(),
+        }
+    }
+
+    /// Get the latest commit on `branch`, or on the default branch if it is `None`
+    pub fn branch_commit(&self, branch: Option<&str>) -> Result<PackageVersion, Error> {
+        let resolve_branch = || match branch {
+            Some(branch) => Ok(branch.to_owned()),
+            None => self.default_branch(),
+        };
+
+        match self {
+            Self::GitHub(repo) => Ok(repo.get_branch_info(&resolve_branch()?)?.commit.into()),
+            Self::GitLab(project) => Ok(project.get_commit(&resolve_branch()?)?.into()),
+            Self::Gitea(repo) => Ok(repo.get_commit(&resolve_branch()?)?.into()),
+            Self::Remote(remote) => {
+                let reference = branch.map_or_else(|| "HEAD".into(), |v| format!("refs/heads/{v}"));
+                Ok(remote.get_ref(&reference)?.into())
+            }
+        }
+    }
+
+    /// Get the commit with the full id `id`
+    pub fn commit(&self, id: &str) -> Result<PackageVersion, Error> {
+        match self {
+            Self::GitHub(repo) => Ok(repo.get_commit(id)?.into()),
+            Self::GitLab(project) => Ok(project.get_commit(id)?.into()),
+            Self::Gitea(repo) => Ok(repo.get_commit(id)?.into()),
+            // NOTE: The date of a commit can't be found without fetching the repository
+            Self::Remote(_) => Ok(PackageVersion::Commit {
+                id: id.into(),
+                date: None,
+            }),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::process::Command;
+
+use jiff::ToSpan;
+use serde::{Deserialize, Serialize};
+
+use crate::{cache::Cache, error::Error, helpers, managed_package::PackageVersion};
+
+#[derive(Serialize, Deserialize)]
+pub struct GitRemoteRef {
+    pub commit: String,
+    /// The branch the reference points to if it is a symbolic reference, such as `HEAD`
+    pub target: Option<String>,
+}
+
+impl From<GitRemoteRef> for PackageVersion {
+    fn from(reference: GitRemoteRef) -> Self {
+        PackageVersion::Commit {
+            id: reference.commit,
+            date: None,
+        }
+    }
+}
+
+/// Turn the url into something that can be used as a cache key
+fn cache_key(url: &str) -> String {
+    let url = url.split_once("://").map_or(url, |(_, v)| v);
+    let url = url.trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    url.split(['/', ':'])
+        .filter(|v| !v.is_empty() && *v != "." && *v != "..")
+        .map(|v| {
+            v.chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || "-_.@".contains(c) {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Any git repository, looked up using `git ls-remote` as there is no API to query
+pub struct GitRemote {
+    url: String,
+    base_key: String,
+    cache: Cache,
+}
+
+impl GitRemote {
+    pub fn new(url: &str, cache: &Cache) -> Self {
+        Self {
+            url: url.into(),
+            base_key: format!("git-remote/{}", cache_key(url)),
+            cache: cache.clone(),
+        }
+    }
+
+    /// Get the commit pointed to by `reference`, such as `HEAD` or `refs/heads/main`
+    pub fn get_ref(&self, reference: &str) -> Result<GitRemoteRef, Error> {
+        self.cache.get_or_insert(
+            &format!("{}/refs/{reference}", self.base_key),
+            1.hour(),
+            || self.ls_remote(reference),
+        )
+    }
+
+    /// The branch `HEAD` points to
+    pub fn default_branch(&self) -> Result<String, Error> {
+        self.get_ref("HEAD")?
+            .target
+            .ok_or_else(|| Error::GitRefNotFound(self.url.clone(), "HEAD".into()))
+    }
+
+    fn ls_remote(&self, reference: &str) -> Result<GitRemoteRef, Error> {
+        let out = helpers::run_command(
+            "git ls-remote",
+            Command::new("git").args(["ls-remote", "--symref", "--", &self.url, reference]),
+        )?;
+
+        let mut commit = None;
+        let mut target = None;
+
+        for line in out.lines() {
+            let Some((value, name)) = line.split_once('\t') else {
+                continue;
+            };
+            if name != reference {
+                continue;
+            }
+            if let Some(symref) = value.strip_prefix("ref: ") {
+                target = Some(symref.strip_prefix("refs/heads/").unwrap_or(symref).into());
+            } else {
+                commit = Some(value.to_owned());
+            }
+        }
+
+        let commit =
+            commit.ok_or_else(|| Error::GitRefNotFound(self.url.clone(), reference.into()))?;
+        Ok(GitRemoteRef { commit, target })
+    }
+}
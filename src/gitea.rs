@@ -0,0 +1,87 @@
+use jiff::ToSpan;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    cache::Cache,
+    error::Error,
+    managed_package::PackageVersion,
+    url_fetch::{encode_path, UrlFetch},
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct GiteaRepoInfo {
+    //pub full_name: String,
+    pub default_branch: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GiteaCommit {
+    pub sha: String,
+    pub commit: GiteaCommitInfo,
+}
+
+impl From<GiteaCommit> for PackageVersion {
+    fn from(commit: GiteaCommit) -> Self {
+        PackageVersion::Commit {
+            id: commit.sha,
+            date: Some(commit.commit.author.date),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GiteaCommitInfo {
+    pub author: GiteaCommitAuthor,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GiteaCommitAuthor {
+    //pub name: String,
+    //pub email: String,
+    pub date: jiff::Timestamp,
+}
+
+/// A repository on a Gitea or Forgejo instance, such as Codeberg
+pub struct GiteaRepo {
+    base_url: String,
+    base_key: String,
+    cache: Cache,
+}
+
+impl GiteaRepo {
+    pub fn new(base_url: &str, owner: &str, repo: &str, cache: &Cache) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        let host = base_url.split_once("://").map_or(base_url, |(_, v)| v);
+        Self {
+            base_url: format!("{base_url}/api/v1/repos/{owner}/{repo}"),
+            base_key: format!("gitea-repo/{host}/{owner}/{repo}"),
+            cache: cache.clone(),
+        }
+    }
+
+    fn get<T: DeserializeOwned>(&self, url: String) -> Result<T, Error> {
+        UrlFetch::new(&url)?.get()?.json()
+    }
+
+    pub fn get_info(&self) -> Result<GiteaRepoInfo, Error> {
+        self.cache
+            .get_or_insert(&format!("{}/info", self.base_key), 1.hour(), || {
+                self.get(self.base_url.clone())
+            })
+    }
+
+    /// Get the commit pointed to by `reference`, which can be a sha, branch or tag
+    pub fn get_commit(&self, reference: &str) -> Result<GiteaCommit, Error> {
+        self.cache.get_or_insert(
+            &format!("{}/commits/{reference}", self.base_key),
+            1.hour(),
+            || {
+                self.get(format!(
+                    "{}/git/commits/{}",
+                    self.base_url,
+                    encode_path(reference)
+                ))
+            },
+        )
+    }
+}
@@ -1,20 +1,20 @@
 use jiff::ToSpan;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::{cache::Cache, error::Error, url_fetch::UrlFetch};
-
-#[derive(Serialize, Deserialize)]
-pub struct GitHubOwner {
-    //pub login: String,
-    //pub url: String,
-}
+use crate::{
+    cache::Cache,
+    error::Error,
+    managed_package::{
+        ChangedCommit, ChangedRelease, PackageChanges, PackageVersion, VersionOrder,
+    },
+    url_fetch::UrlFetch,
+};
 
 #[derive(Serialize, Deserialize)]
 pub struct GitHubRepoInfo {
     //pub name: String,
     //pub full_name: String,
     //pub private: bool,
-    //pub owner: GitHubOwner,
     //pub fork: bool,
     pub default_branch: String,
 }
@@ -31,19 +31,98 @@ pub struct GitHubCommit {
     pub commit: GitHubCommitInfo,
 }
 
+impl From<GitHubCommit> for PackageVersion {
+    fn from(commit: GitHubCommit) -> Self {
+        PackageVersion::Commit {
+            id: commit.sha,
+            date: Some(commit.commit.author.date),
+        }
+    }
+}
+
+impl From<GitHubCommit> for ChangedCommit {
+    fn from(commit: GitHubCommit) -> Self {
+        let GitHubCommitInfo { author, message } = commit.commit;
+        Self {
+            id: commit.sha,
+            summary: message.lines().next().unwrap_or_default().into(),
+            author: author.name,
+            date: Some(author.date),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GitHubCommitInfo {
     pub author: GitHubCommitAuthor,
     //pub committer: GitHubCommitAuthor,
+    #[serde(default)]
+    pub message: String,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct GitHubCommitAuthor {
-    //pub name: String,
+    #[serde(default)]
+    pub name: String,
     //pub email: String,
     pub date: jiff::Timestamp,
 }
 
+/// How the head of a comparison relates to the base
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GitHubCompareStatus {
+    Identical,
+    Ahead,
+    Behind,
+    Diverged,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GitHubComparison {
+    /// Missing in comparisons cached by older versions of logix
+    #[serde(default)]
+    pub status: Option<GitHubCompareStatus>,
+    //pub ahead_by: u64,
+    //pub behind_by: u64,
+    /// The commits reachable from the head but not the base, oldest first
+    pub commits: Vec<GitHubCommit>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GitHubRelease {
+    pub tag_name: String,
+    /// The release notes
+    #[serde(default)]
+    pub body: Option<String>,
+    pub draft: bool,
+    pub prerelease: bool,
+    pub assets: Vec<GitHubAsset>,
+}
+
+impl GitHubRelease {
+    /// The version of the release, `None` if the tag isn't a semver version
+    pub fn version(&self) -> Option<semver::Version> {
+        let tag = self.tag_name.strip_prefix('v').unwrap_or(&self.tag_name);
+        semver::Version::parse(tag).ok()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GitHubAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl GitHubAsset {
+    pub fn download(&self) -> Result<Vec<u8>, Error> {
+        Ok(UrlFetch::new(&self.browser_download_url)?
+            .follow_location()?
+            .get()?
+            .into_bytes())
+    }
+}
+
 pub struct GitHubRepo {
     base_url: String,
     base_key: String,
@@ -70,10 +149,114 @@ impl GitHubRepo {
             })
     }
 
+    /// Get the commit pointed to by `reference`, which can be a sha, branch or tag
+    pub fn get_commit(&self, reference: &str) -> Result<GitHubCommit, Error> {
+        self.cache.get_or_insert(
+            &format!("{}/commits/{reference}", self.base_key),
+            1.hour(),
+            || self.get(format!("{}/commits/{reference}", self.base_url)),
+        )
+    }
+
+    /// Get the most recent releases, drafts are not included
+    pub fn get_releases(&self) -> Result<Vec<GitHubRelease>, Error> {
+        let releases: Vec<GitHubRelease> =
+            self.cache
+                .get_or_insert(&format!("{}/releases", self.base_key), 1.hour(), || {
+                    self.get(format!("{}/releases?per_page=100", self.base_url))
+                })?;
+        Ok(releases.into_iter().filter(|v| !v.draft).collect())
+    }
+
+    /// Get the commits between `base` and `head`
+    pub fn compare(&self, base: &str, head: &str) -> Result<GitHubComparison, Error> {
+        // NOTE: The commits between two commit ids never change
+        self.cache.get_or_insert(
+            &format!("{}/compare/{base}/{head}", self.base_key),
+            30.days(),
+            || self.get(format!("{}/compare/{base}...{head}", self.base_url)),
+        )
+    }
+
+    /// Compare two versions, commits are ordered by their history using the
+    /// compare API instead of their dates
+    pub fn compare_versions(
+        &self,
+        a: &PackageVersion,
+        b: &PackageVersion,
+    ) -> Result<VersionOrder, Error> {
+        match (a, b) {
+            (PackageVersion::Commit { id: base, .. }, PackageVersion::Commit { id: head, .. })
+                if !a.is_same(b) =>
+            {
+                Ok(match self.compare(base, head)?.status {
+                    Some(GitHubCompareStatus::Identical) => VersionOrder::Same,
+                    Some(GitHubCompareStatus::Ahead) => VersionOrder::Older,
+                    Some(GitHubCompareStatus::Behind) => VersionOrder::Newer,
+                    Some(GitHubCompareStatus::Diverged) => VersionOrder::Diverged,
+                    None => a.compare(b),
+                })
+            }
+            _ => Ok(a.compare(b)),
+        }
+    }
+
+    /// Describes what changed between two versions, commits are compared using
+    /// the compare API and semver versions using the notes of the releases in
+    /// between. Pre-releases are only included if the version requirement `req`
+    /// allows them. Returns `None` if the versions can't be compared
+    pub fn changes(
+        &self,
+        from: &PackageVersion,
+        to: &PackageVersion,
+        req: Option<&semver::VersionReq>,
+    ) -> Result<Option<PackageChanges>, Error> {
+        match (from, to) {
+            (PackageVersion::Commit { id: base, .. }, PackageVersion::Commit { id: head, .. }) => {
+                let commits = self.compare(base, head)?.commits;
+                Ok(Some(PackageChanges::Commits(
+                    commits.into_iter().map(ChangedCommit::from).collect(),
+                )))
+            }
+            (from, PackageVersion::Semver(to)) => {
+                let from = match from {
+                    PackageVersion::Semver(from) => Some(from),
+                    PackageVersion::None
+                    | PackageVersion::Commit { .. }
+                    | PackageVersion::Other(_) => None,
+                };
+
+                // NOTE: Without an installed version only the notes of the target are relevant
+                let mut releases = self
+                    .get_releases()?
+                    .into_iter()
+                    .filter(|release| !release.prerelease)
+                    .filter_map(|release| {
+                        let version = release.version()?;
+                        let allowed =
+                            version.pre.is_empty() || req.is_some_and(|req| req.matches(&version));
+                        let include = allowed
+                            && version <= *to
+                            && from.map_or(version == *to, |from| version > *from);
+                        include.then(|| ChangedRelease {
+                            version,
+                            tag: release.tag_name,
+                            notes: release.body.unwrap_or_default(),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                releases.sort_by(|a, b| b.version.cmp(&a.version));
+                Ok(Some(PackageChanges::Releases(releases)))
+            }
+            _ => Ok(None),
+        }
+    }
+
     pub fn get_branch_info(&self, branch: &str) -> Result<GitHubBranchInfo, Error> {
-        self.cache
-            .get_or_insert(&format!("{}/branch_info", self.base_key), 1.hour(), || {
-                self.get(format!("{}/branches/{branch}", self.base_url))
-            })
+        self.cache.get_or_insert(
+            &format!("{}/branches/{branch}", self.base_key),
+            1.hour(),
+            || self.get(format!("{}/branches/{branch}", self.base_url)),
+        )
     }
 }
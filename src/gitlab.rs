@@ -0,0 +1,75 @@
+use jiff::ToSpan;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    cache::Cache,
+    error::Error,
+    managed_package::PackageVersion,
+    url_fetch::{encode_path, UrlFetch},
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct GitLabProjectInfo {
+    //pub path_with_namespace: String,
+    pub default_branch: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GitLabCommit {
+    pub id: String,
+    pub authored_date: jiff::Timestamp,
+}
+
+impl From<GitLabCommit> for PackageVersion {
+    fn from(commit: GitLabCommit) -> Self {
+        PackageVersion::Commit {
+            id: commit.id,
+            date: Some(commit.authored_date),
+        }
+    }
+}
+
+pub struct GitLabProject {
+    base_url: String,
+    base_key: String,
+    cache: Cache,
+}
+
+impl GitLabProject {
+    /// The `project` is the full path of the project, such as `group/subgroup/repo`
+    pub fn new(base_url: &str, project: &str, cache: &Cache) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        let host = base_url.split_once("://").map_or(base_url, |(_, v)| v);
+        Self {
+            base_url: format!("{base_url}/api/v4/projects/{}", encode_path(project)),
+            base_key: format!("gitlab-project/{host}/{project}"),
+            cache: cache.clone(),
+        }
+    }
+
+    fn get<T: DeserializeOwned>(&self, url: String) -> Result<T, Error> {
+        UrlFetch::new(&url)?.get()?.json()
+    }
+
+    pub fn get_info(&self) -> Result<GitLabProjectInfo, Error> {
+        self.cache
+            .get_or_insert(&format!("{}/info", self.base_key), 1.hour(), || {
+                self.get(self.base_url.clone())
+            })
+    }
+
+    /// Get the commit pointed to by `reference`, which can be a sha, branch or tag
+    pub fn get_commit(&self, reference: &str) -> Result<GitLabCommit, Error> {
+        self.cache.get_or_insert(
+            &format!("{}/commits/{reference}", self.base_key),
+            1.hour(),
+            || {
+                self.get(format!(
+                    "{}/repository/commits/{}",
+                    self.base_url,
+                    encode_path(reference)
+                ))
+            },
+        )
+    }
+}
@@ -0,0 +1,75 @@
+use jiff::ToSpan;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache::Cache, error::Error, managed_package::parse_loose_version, url_fetch::UrlFetch,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct GoModuleInfo {
+    #[serde(rename = "Version")]
+    pub version: String,
+}
+
+/// Escape the module path as required by the proxy, upper case letters are
+/// replaced by `!` followed by the lower case letter
+fn escape_path(path: &str) -> String {
+    let mut ret = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c.is_ascii_uppercase() {
+            ret.push('!');
+            ret.push(c.to_ascii_lowercase());
+        } else {
+            ret.push(c);
+        }
+    }
+    ret
+}
+
+/// A module on the go module proxy at `proxy.golang.org`
+pub struct GoModule {
+    base_url: String,
+    base_key: String,
+    cache: Cache,
+}
+
+impl GoModule {
+    pub fn new(module: &str, cache: &Cache) -> Self {
+        Self {
+            base_url: format!("https://proxy.golang.org/{}/@v", escape_path(module)),
+            base_key: format!("go-proxy/{module}"),
+            cache: cache.clone(),
+        }
+    }
+
+    /// Get the tagged versions of the module
+    pub fn get_versions(&self) -> Result<Vec<semver::Version>, Error> {
+        let versions: Vec<String> =
+            self.cache
+                .get_or_insert(&format!("{}/list", self.base_key), 1.hour(), || {
+                    let data = UrlFetch::new(&format!("{}/list", self.base_url))?
+                        .get()?
+                        .into_bytes();
+                    Ok(String::from_utf8_lossy(&data)
+                        .lines()
+                        .map(|v| v.trim().to_owned())
+                        .filter(|v| !v.is_empty())
+                        .collect())
+                })?;
+
+        Ok(versions
+            .iter()
+            .filter_map(|v| parse_loose_version(v))
+            .collect())
+    }
+
+    /// Get the latest version, this is a pseudo-version if the module has no tagged versions
+    pub fn get_latest(&self) -> Result<GoModuleInfo, Error> {
+        self.cache
+            .get_or_insert(&format!("{}/latest", self.base_key), 1.hour(), || {
+                UrlFetch::new(&format!("{}/latest", self.base_url))?
+                    .get()?
+                    .json()
+            })
+    }
+}
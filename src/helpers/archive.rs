@@ -0,0 +1,210 @@
+use std::{
+    io::{Cursor, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use crate::error::Error;
+
+/// The format of a downloaded file, decided by its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    TarGz,
+    Zip,
+    /// Not an archive, such as a binary released as is
+    Plain,
+}
+
+impl ArchiveKind {
+    pub fn from_name(name: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Self::TarGz
+        } else if name.ends_with(".zip") {
+            Self::Zip
+        } else {
+            Self::Plain
+        }
+    }
+}
+
+fn has_file_name(path: &Path, file_name: &str) -> bool {
+    path.file_name().is_some_and(|v| v == file_name)
+}
+
+/// Find the file called `file_name` anywhere in the archive `name` and return its content
+pub fn extract_file(name: &str, data: &[u8], file_name: &str) -> Result<Vec<u8>, Error> {
+    let extract_error = |e: std::io::Error| Error::ExtractArchive(name.into(), e.to_string());
+
+    match ArchiveKind::from_name(name) {
+        ArchiveKind::TarGz => {
+            let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data));
+            for entry in archive.entries().map_err(extract_error)? {
+                let mut entry = entry.map_err(extract_error)?;
+                if entry.header().entry_type().is_file()
+                    && has_file_name(&entry.path().map_err(extract_error)?, file_name)
+                {
+                    let mut ret = Vec::new();
+                    entry.read_to_end(&mut ret).map_err(extract_error)?;
+                    return Ok(ret);
+                }
+            }
+        }
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(Cursor::new(data))
+                .map_err(|e| Error::ExtractArchive(name.into(), e.to_string()))?;
+            for i in 0..archive.len() {
+                let mut file = archive
+                    .by_index(i)
+                    .map_err(|e| Error::ExtractArchive(name.into(), e.to_string()))?;
+                if file.is_file()
+                    && file
+                        .enclosed_name()
+                        .is_some_and(|path| has_file_name(&path, file_name))
+                {
+                    let mut ret = Vec::new();
+                    file.read_to_end(&mut ret).map_err(extract_error)?;
+                    return Ok(ret);
+                }
+            }
+        }
+        ArchiveKind::Plain => return Ok(data.to_vec()),
+    }
+
+    Err(Error::FileNotInArchive(name.into(), file_name.into()))
+}
+
+/// Remove the first `strip_components` components of `path`, returns `None` if
+/// nothing remains or if the path tries to escape the destination directory
+fn strip_path(path: &Path, strip_components: usize) -> Option<PathBuf> {
+    let mut ret = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(v) => ret.push(v),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    let ret = ret.components().skip(strip_components).collect::<PathBuf>();
+    (ret != PathBuf::new()).then_some(ret)
+}
+
+/// Join `rel_path` to `dest`, checking that each parent is a real directory so a
+/// hostile archive can't write outside `dest` through a symlink it created earlier.
+/// Missing parents are created when `create_parents` is set
+fn enclosed_path(dest: &Path, rel_path: &Path, create_parents: bool) -> std::io::Result<PathBuf> {
+    let mut path = dest.to_path_buf();
+    let mut components = rel_path.components().peekable();
+    while let Some(component) = components.next() {
+        path.push(component);
+        if components.peek().is_none() {
+            break;
+        }
+        match path.symlink_metadata() {
+            Ok(metadata) if metadata.is_dir() => {}
+            Ok(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{rel_path:?} is not inside a directory of the archive"),
+                ))
+            }
+            Err(e) if create_parents && e.kind() == std::io::ErrorKind::NotFound => {
+                std::fs::create_dir(&path)?
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(path)
+}
+
+/// The path to extract `rel_path` to, a symlink from an earlier entry is replaced
+/// rather than followed
+fn output_path(dest: &Path, rel_path: &Path) -> std::io::Result<PathBuf> {
+    let path = enclosed_path(dest, rel_path, true)?;
+    if path.symlink_metadata().is_ok_and(|v| v.is_symlink()) {
+        std::fs::remove_file(&path)?;
+    }
+    Ok(path)
+}
+
+/// Extract the archive `name` into `dest`, removing the first `strip_components`
+/// components of each path. Entries are never written outside `dest`
+pub fn extract_to_dir(
+    name: &str,
+    data: &[u8],
+    dest: &Path,
+    strip_components: usize,
+) -> Result<(), Error> {
+    let extract_error = |e: std::io::Error| Error::ExtractArchive(name.into(), e.to_string());
+
+    std::fs::create_dir_all(dest).map_err(extract_error)?;
+
+    match ArchiveKind::from_name(name) {
+        ArchiveKind::TarGz => {
+            let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data));
+            for entry in archive.entries().map_err(extract_error)? {
+                let mut entry = entry.map_err(extract_error)?;
+                let Some(rel_path) =
+                    strip_path(&entry.path().map_err(extract_error)?, strip_components)
+                else {
+                    continue;
+                };
+                let path = output_path(dest, &rel_path).map_err(extract_error)?;
+
+                if entry.header().entry_type().is_hard_link() {
+                    // NOTE: The target is relative to the archive, not to the working directory
+                    let target = entry
+                        .link_name()
+                        .map_err(extract_error)?
+                        .and_then(|target| strip_path(&target, strip_components))
+                        .ok_or_else(|| {
+                            Error::ExtractArchive(
+                                name.into(),
+                                format!("invalid hard link {rel_path:?}"),
+                            )
+                        })?;
+                    let target = enclosed_path(dest, &target, false).map_err(extract_error)?;
+                    std::fs::hard_link(target, &path).map_err(extract_error)?;
+                    continue;
+                }
+                entry.unpack(&path).map_err(extract_error)?;
+            }
+        }
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(Cursor::new(data))
+                .map_err(|e| Error::ExtractArchive(name.into(), e.to_string()))?;
+            for i in 0..archive.len() {
+                let mut file = archive
+                    .by_index(i)
+                    .map_err(|e| Error::ExtractArchive(name.into(), e.to_string()))?;
+                let Some(rel_path) = file
+                    .enclosed_name()
+                    .and_then(|path| strip_path(&path, strip_components))
+                else {
+                    continue;
+                };
+                let path = output_path(dest, &rel_path).map_err(extract_error)?;
+                if file.is_dir() {
+                    std::fs::create_dir_all(&path).map_err(extract_error)?;
+                    continue;
+                }
+                let mut out = std::fs::File::create(&path).map_err(extract_error)?;
+                std::io::copy(&mut file, &mut out).map_err(extract_error)?;
+
+                #[cfg(unix)]
+                if let Some(mode) = file.unix_mode() {
+                    use std::os::unix::fs::PermissionsExt;
+                    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode))
+                        .map_err(extract_error)?;
+                }
+            }
+        }
+        ArchiveKind::Plain => {
+            return Err(Error::ExtractArchive(
+                name.into(),
+                "unknown archive format, expected `.tar.gz`, `.tgz` or `.zip`".into(),
+            ))
+        }
+    }
+
+    Ok(())
+}
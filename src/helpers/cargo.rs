@@ -1,21 +1,261 @@
-use std::{collections::HashMap, fmt, process::Command};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
+use jiff::ToSpan;
 
 use crate::{
-    cache::Cache, config::RustCrateSource, error::Error, github::GitHubRepo,
-    managed_package::PackageVersion,
+    cache::Cache,
+    config::{
+        CargoFlag, CargoInstallOptions, CargoInstaller, Package, PackageDefaults, RustCrateSource,
+    },
+    crates_io::CratesIoCrate,
+    error::Error,
+    git_host::GitHost,
+    github::GitHubRepo,
+    installed_packages::{InstalledKind, InstalledPackage},
+    managed_package::{
+        parse_version_req, LatestVersion, ManagedPackage, PackageChanges, PackageDetails,
+        PackageVersion, PackageWarning, VersionOrder,
+    },
+    package_provider::{PackageProvider, ProviderContext},
 };
 
+use super::run_status;
+
 pub struct CrateSpec<'a> {
     name: CrateName,
     source: Option<&'a RustCrateSource>,
+    options: &'a CargoInstallOptions,
+    installer: CargoInstaller,
+    version_req: Option<semver::VersionReq>,
 }
 
 impl<'a> CrateSpec<'a> {
-    pub fn new(name: &str, source: Option<&'a RustCrateSource>) -> Self {
-        Self {
+    pub fn new(
+        name: &str,
+        source: Option<&'a RustCrateSource>,
+        options: Option<&'a CargoInstallOptions>,
+        version_req: Option<&str>,
+        defaults: &PackageDefaults,
+    ) -> Result<Self, Error> {
+        let options = options.unwrap_or(CargoInstallOptions::EMPTY);
+        let version_req = parse_version_req(name, version_req)?;
+
+        if version_req.is_some() && source.is_some() {
+            log::warn!("Ignoring the version requirement of {name:?} as it is not from crates.io");
+        }
+
+        Ok(Self {
             name: CrateName::new(name),
             source,
+            options,
+            installer: options
+                .installer
+                .or(defaults.cargo_installer)
+                .unwrap_or(CargoInstaller::Cargo),
+            version_req,
+        })
+    }
+
+    fn from_package(package: &ManagedPackage<'a>) -> Result<Self, Error> {
+        let Package::RustCrate {
+            crate_name,
+            source,
+            install_options,
+            version,
+            ..
+        } = package.package()
+        else {
+            return Err(Error::UnsupportedPackage(package.name().into(), "cargo"));
+        };
+        Self::new(
+            crate_name.as_deref().unwrap_or(package.name()),
+            source.as_ref(),
+            install_options.as_ref(),
+            version.as_deref(),
+            package.defaults(),
+        )
+    }
+
+    /// The name of the crate as recognized by cargo
+    pub fn crate_name(&self) -> &str {
+        &self.name.0
+    }
+
+    /// The features declared in the config
+    pub fn declared_features(&self) -> CrateFeatures {
+        CrateFeatures::new(
+            self.options
+                .features
+                .iter()
+                .flatten()
+                .map(|v| v.to_string())
+                .collect(),
+            self.options.has_flag(CargoFlag::AllFeatures),
+            self.options.has_flag(CargoFlag::NoDefaultFeatures),
+        )
+    }
+
+    /// Check if the crate is built with the default options, which is required
+    /// for the prebuilt binaries of `cargo binstall`
+    fn is_default_build(&self) -> bool {
+        let CargoInstallOptions {
+            installer: _,
+            features,
+            flags,
+            bins,
+            profile,
+            toolchain,
+        } = self.options;
+
+        // NOTE: Prebuilt binaries are only available for crates.io using the default build options
+        self.source.is_none()
+            && features.as_ref().is_none_or(|v| v.is_empty())
+            && flags
+                .iter()
+                .flatten()
+                .all(|flag| matches!(flag, CargoFlag::Locked))
+            && bins.is_none()
+            && profile.is_none()
+            && toolchain.is_none()
+    }
+
+    /// The arguments to `cargo` that installs `version` of the crate, along with a
+    /// description of the command. `cargo binstall` is used if it is the configured
+    /// installer, it is available and the crate doesn't need to be built from source
+    pub fn install_args(
+        &self,
+        version: &PackageVersion,
+        binstall_available: bool,
+    ) -> (&'static str, Vec<String>) {
+        match self.installer {
+            CargoInstaller::Binstall if binstall_available && self.is_default_build() => {
+                ("cargo binstall", self.binstall_args(version))
+            }
+            CargoInstaller::Binstall if binstall_available => {
+                log::info!(
+                    "Using cargo install for {:?} as it needs to be built from source",
+                    self.name.0
+                );
+                ("cargo install", self.cargo_install_args(version))
+            }
+            CargoInstaller::Binstall | CargoInstaller::Cargo => {
+                ("cargo install", self.cargo_install_args(version))
+            }
+        }
+    }
+
+    fn binstall_args(&self, version: &PackageVersion) -> Vec<String> {
+        let mut args = vec!["binstall".to_string(), "--no-confirm".into()];
+
+        if self.options.has_flag(CargoFlag::Locked) {
+            args.push("--locked".into());
+        }
+
+        if let PackageVersion::Semver(version) = version {
+            args.push(format!("{}@{version}", self.name.0));
+        } else {
+            args.push(self.name.0.clone());
+        }
+        args
+    }
+
+    fn cargo_install_args(&self, version: &PackageVersion) -> Vec<String> {
+        let CargoInstallOptions {
+            installer: _,
+            features,
+            flags,
+            bins,
+            profile,
+            toolchain,
+        } = self.options;
+
+        let mut args = Vec::new();
+
+        if let Some(toolchain) = toolchain {
+            args.push(format!("+{toolchain}"));
+        }
+
+        args.push("install".into());
+
+        if let Some(features) = features.as_ref().filter(|v| !v.is_empty()) {
+            args.push("--features".into());
+            args.push(features.iter().map(|v| &**v).collect::<Vec<_>>().join(","));
+        }
+
+        for flag in flags.iter().flatten() {
+            args.push(
+                match flag {
+                    CargoFlag::Locked => "--locked",
+                    CargoFlag::NoDefaultFeatures => "--no-default-features",
+                    CargoFlag::AllFeatures => "--all-features",
+                }
+                .into(),
+            );
         }
+
+        for bin in bins.iter().flatten() {
+            args.push("--bin".into());
+            args.push(bin.to_string());
+        }
+
+        if let Some(profile) = profile {
+            args.push("--profile".into());
+            args.push(profile.to_string());
+        }
+
+        // NOTE: Pinning a git source to a commit is used to install the version from the lockfile
+        let commit = match version {
+            PackageVersion::Commit { id, .. } => Some(id.as_str()),
+            PackageVersion::None | PackageVersion::Semver(_) | PackageVersion::Other(_) => None,
+        };
+
+        if let Some(source) = self.source {
+            args.push("--git".into());
+            args.push(source.git_url());
+        }
+
+        let reference = match self.source {
+            Some(
+                RustCrateSource::GithubBranch { branch, .. }
+                | RustCrateSource::GitLabBranch { branch, .. }
+                | RustCrateSource::GiteaBranch { branch, .. }
+                | RustCrateSource::CodebergBranch { branch, .. },
+            ) => Some(match commit {
+                Some(commit) => ("--rev", commit.to_string()),
+                None => ("--branch", branch.to_string()),
+            }),
+            Some(RustCrateSource::GitBranch { url: _, branch }) => match (commit, branch) {
+                (Some(commit), _) => Some(("--rev", commit.to_string())),
+                (None, Some(branch)) => Some(("--branch", branch.to_string())),
+                (None, None) => None,
+            },
+            Some(RustCrateSource::GithubTag { tag, .. }) => Some(match commit {
+                Some(commit) => ("--rev", commit.to_string()),
+                None => ("--tag", tag.to_string()),
+            }),
+            Some(RustCrateSource::GithubRevision { revision, .. }) => {
+                Some(("--rev", commit.unwrap_or(revision).to_string()))
+            }
+            None => match version {
+                PackageVersion::Semver(version) => Some(("--version", format!("={version}"))),
+                PackageVersion::None | PackageVersion::Commit { .. } | PackageVersion::Other(_) => {
+                    None
+                }
+            },
+        };
+        if let Some((arg, value)) = reference {
+            args.push(arg.into());
+            args.push(value);
+        }
+
+        args.push(self.name.0.clone());
+        args
     }
 
     pub fn cache_name(&self) -> impl fmt::Display {
@@ -42,11 +282,33 @@ impl<'a> CrateSpec<'a> {
                 "github.com/{owner}/{repo}/revs/{revision}/crates/{}",
                 self.name.0
             ),
+            Some(
+                source @ (RustCrateSource::GitLabBranch { branch, .. }
+                | RustCrateSource::GiteaBranch { branch, .. }
+                | RustCrateSource::CodebergBranch { branch, .. }),
+            ) => format!(
+                "{}/branches/{branch}/crates/{}",
+                repo_cache_name(&source.git_url()),
+                self.name.0
+            ),
+            Some(source @ RustCrateSource::GitBranch { url: _, branch }) => format!(
+                "{}/branches/{}/crates/{}",
+                repo_cache_name(&source.git_url()),
+                branch.as_deref().unwrap_or("HEAD"),
+                self.name.0
+            ),
             None => format!("crates.io/{}", self.name.0),
         }
     }
 }
 
+/// Turn a repository url such as `https://gitlab.com/group/repo.git` into `gitlab.com/group/repo`
+fn repo_cache_name(url: &str) -> String {
+    let url = url.split_once("://").map_or(url, |(_, v)| v);
+    let url = url.trim_end_matches('/');
+    url.strip_suffix(".git").unwrap_or(url).replace(':', "/")
+}
+
 #[derive(PartialEq, Eq, Hash, Debug)]
 struct CrateName(String);
 
@@ -60,15 +322,260 @@ impl CrateName {
     }
 }
 
+/// The git reference a crate was installed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitReference {
+    DefaultBranch,
+    Branch(String),
+    Tag(String),
+    Rev(String),
+}
+
+/// A git source a crate was installed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitSource {
+    pub url: String,
+    pub reference: GitReference,
+    /// The commit that was installed, abbreviated when read from `cargo install --list`
+    pub commit: String,
+}
+
+impl GitSource {
+    /// Parse the source, such as `https://github.com/owner/repo?tag=v1.0#a1b2c3d4`
+    pub fn parse(source: &str) -> Option<Self> {
+        let (url, commit) = source.split_once('#')?;
+        let (url, reference) = match url.split_once('?') {
+            Some((url, query)) => (
+                url,
+                match query.split_once('=')? {
+                    ("branch", name) => GitReference::Branch(name.into()),
+                    ("tag", name) => GitReference::Tag(name.into()),
+                    ("rev", name) => GitReference::Rev(name.into()),
+                    _ => return None,
+                },
+            ),
+            None => (url, GitReference::DefaultBranch),
+        };
+        Some(Self {
+            url: url.into(),
+            reference,
+            commit: commit.into(),
+        })
+    }
+
+    fn is_github_repo(&self, owner: &str, repo: &str) -> bool {
+        self.is_repo(&format!("https://github.com/{owner}/{repo}"))
+    }
+
+    /// Check if the crate was installed from the repository at `url`
+    fn is_repo(&self, url: &str) -> bool {
+        let normalize = |url: &str| {
+            let url = url.trim_end_matches('/');
+            url.strip_suffix(".git").unwrap_or(url).to_ascii_lowercase()
+        };
+        normalize(&self.url) == normalize(url)
+    }
+
+    /// Check if the crate was installed using `--rev` with the installed commit,
+    /// which is how versions from the lockfile are installed
+    fn is_pinned(&self) -> bool {
+        match &self.reference {
+            GitReference::Rev(rev) => rev.starts_with(&self.commit) || self.commit.starts_with(rev),
+            GitReference::DefaultBranch | GitReference::Branch(_) | GitReference::Tag(_) => false,
+        }
+    }
+}
+
+/// Where an installed crate came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrateSource {
+    /// A registry such as crates.io, contains the url of the index
+    Registry(String),
+    Git(GitSource),
+    /// A local directory
+    Path(String),
+}
+
+impl CrateSource {
+    /// Parse a source as written in `.crates2.json`, such as `registry+https://...`
+    fn parse_source_id(source: &str) -> Option<Self> {
+        let (kind, url) = source.split_once('+')?;
+        match kind {
+            "registry" | "sparse" => Some(Self::Registry(url.into())),
+            "git" => GitSource::parse(url).map(Self::Git),
+            "path" => Some(Self::Path(url.into())),
+            _ => None,
+        }
+    }
+
+    /// Parse a source as printed by `cargo install --list`
+    fn parse_listed(source: &str) -> Self {
+        GitSource::parse(source)
+            .map(Self::Git)
+            .unwrap_or_else(|| Self::Path(source.into()))
+    }
+}
+
+/// A crate installed using `cargo install`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledCrate {
+    pub name: String,
+    pub version: semver::Version,
+    pub source: CrateSource,
+    /// The installed binaries
+    pub bins: Vec<String>,
+    /// How the crate was built, only known if read from `.crates2.json`
+    pub build: Option<CrateBuild>,
+}
+
+/// The features of a crate, either as installed or as declared in the config
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CrateFeatures {
+    /// The features that was explicitly enabled, sorted
+    pub features: Vec<String>,
+    pub all_features: bool,
+    pub no_default_features: bool,
+}
+
+impl CrateFeatures {
+    fn new(mut features: Vec<String>, all_features: bool, no_default_features: bool) -> Self {
+        features.sort();
+        features.dedup();
+        Self {
+            features,
+            all_features,
+            no_default_features,
+        }
+    }
+}
+
+impl fmt::Display for CrateFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            features,
+            all_features,
+            no_default_features,
+        } = self;
+        write!(f, "[{}]", features.join(", "))?;
+        if *all_features {
+            write!(f, " --all-features")?;
+        }
+        if *no_default_features {
+            write!(f, " --no-default-features")?;
+        }
+        Ok(())
+    }
+}
+
+/// How an installed crate was built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateBuild {
+    pub features: CrateFeatures,
+    pub profile: String,
+}
+
+#[derive(serde::Deserialize)]
+struct Crates2 {
+    installs: HashMap<String, Crates2Entry>,
+}
+
+#[derive(serde::Deserialize)]
+struct Crates2Entry {
+    #[serde(default)]
+    bins: Vec<String>,
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(default)]
+    all_features: bool,
+    #[serde(default)]
+    no_default_features: bool,
+    profile: String,
+}
+
 pub struct CargoState {
-    crates: HashMap<CrateName, PackageVersion>,
+    cargo_home: PathBuf,
+    crates: HashMap<CrateName, InstalledCrate>,
+    binstall_available: OnceLock<bool>,
 }
 
 impl CargoState {
-    pub fn init() -> Result<Self, Error> {
+    pub fn init(cargo_home: &Path) -> Result<Self, Error> {
         let mut ret = CargoState {
+            cargo_home: cargo_home.into(),
             crates: HashMap::new(),
+            binstall_available: OnceLock::new(),
+        };
+
+        let crates = match Self::read_crates2(cargo_home) {
+            Ok(Some(crates)) => crates,
+            Ok(None) => {
+                log::debug!("No .crates2.json in {cargo_home:?}, using `cargo install --list`");
+                Self::list_installed()?
+            }
+            Err(e) => {
+                log::warn!("{e}, using `cargo install --list`");
+                Self::list_installed()?
+            }
+        };
+
+        for info in crates {
+            ret.crates.insert(CrateName::new(&info.name), info);
+        }
+
+        Ok(ret)
+    }
+
+    /// Read the installed crates from `.crates2.json`, returns `None` if the file is missing
+    fn read_crates2(cargo_home: &Path) -> Result<Option<Vec<InstalledCrate>>, Error> {
+        let path = cargo_home.join(".crates2.json");
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::ReadCargoState(path, e.to_string())),
         };
+        let crates2: Crates2 = serde_json::from_slice(&data)
+            .map_err(|e| Error::ReadCargoState(path.clone(), e.to_string()))?;
+
+        let mut ret = Vec::with_capacity(crates2.installs.len());
+
+        for (id, entry) in crates2.installs {
+            let parsed = id.split_once(' ').and_then(|(name, remain)| {
+                let (version, source) = remain.split_once(' ')?;
+                let source = source.strip_prefix('(')?.strip_suffix(')')?;
+                Some((
+                    name,
+                    semver::Version::parse(version).ok()?,
+                    CrateSource::parse_source_id(source)?,
+                ))
+            });
+            let Some((name, version, source)) = parsed else {
+                log::warn!("Ignoring unknown package id {id:?} in {path:?}");
+                continue;
+            };
+            let Crates2Entry {
+                bins,
+                features,
+                all_features,
+                no_default_features,
+                profile,
+            } = entry;
+            ret.push(InstalledCrate {
+                name: name.into(),
+                version,
+                source,
+                bins,
+                build: Some(CrateBuild {
+                    features: CrateFeatures::new(features, all_features, no_default_features),
+                    profile,
+                }),
+            });
+        }
+
+        Ok(Some(ret))
+    }
+
+    /// Parse the output of `cargo install --list`, only used if `.crates2.json` is unavailable
+    fn list_installed() -> Result<Vec<InstalledCrate>, Error> {
         let res = Command::new("cargo")
             .arg("install")
             .arg("--list")
@@ -83,33 +590,133 @@ impl CargoState {
             })
             .map_err(|e| Error::ShellCommandFailed("cargo install --list", e.to_string()))?;
 
-        let re = regex::Regex::new(r"^(\S+)\s+(\S+)(\s+\([^)]+\))?:$").unwrap();
+        let re = regex::Regex::new(r"^(\S+)\s+v(\S+)(\s+\(([^)]+)\))?:$").unwrap();
+        let mut ret = Vec::<InstalledCrate>::new();
 
         for line in String::from_utf8_lossy(&res).lines() {
             if let Some(m) = re.captures(line) {
-                if let Some(_path) = m.get(3) {
-                    // TODO: Include those with a local clone?
-                } else {
-                    ret.crates
-                        .insert(CrateName::new(&m[1]), parse_version(&m[2]));
+                let Ok(version) = semver::Version::parse(&m[2]) else {
+                    log::warn!("Ignoring crate with unknown version: {line:?}");
+                    continue;
+                };
+                ret.push(InstalledCrate {
+                    name: m[1].into(),
+                    version,
+                    source: m.get(4).map_or_else(
+                        || {
+                            CrateSource::Registry(
+                                "https://github.com/rust-lang/crates.io-index".into(),
+                            )
+                        },
+                        |source| CrateSource::parse_listed(source.as_str()),
+                    ),
+                    bins: Vec::new(),
+                    build: None,
+                });
+            } else if let Some(bin) = line.strip_prefix("    ") {
+                if let Some(last) = ret.last_mut() {
+                    last.bins.push(bin.into());
                 }
-            } else if !line.starts_with(' ') {
-                todo!("{line:?}")
+            } else {
+                log::warn!("Ignoring unexpected line from `cargo install --list`: {line:?}");
             }
         }
 
         Ok(ret)
     }
 
-    pub fn package_version(&self, spec: &CrateSpec) -> PackageVersion {
-        self.crates
-            .get(&spec.name)
-            .cloned()
-            .unwrap_or(PackageVersion::None)
+    /// Returns information about the installed crate, regardless of where it was installed from
+    pub fn installed_crate(&self, spec: &CrateSpec) -> Option<&InstalledCrate> {
+        self.crates.get(&spec.name)
+    }
+
+    /// Returns all the crates installed using `cargo install`
+    pub fn installed_crates(&self) -> impl Iterator<Item = &InstalledCrate> {
+        self.crates.values()
+    }
+
+    /// Returns the installed version of the crate, or [PackageVersion::None] if it
+    /// is not installed from the source in the `spec`
+    pub fn package_version(
+        &self,
+        spec: &CrateSpec,
+        cache: &Cache,
+    ) -> Result<PackageVersion, Error> {
+        let Some(installed) = self.installed_crate(spec) else {
+            return Ok(PackageVersion::None);
+        };
+
+        match (spec.source, &installed.source) {
+            (None, CrateSource::Registry(_)) => {
+                Ok(PackageVersion::Semver(installed.version.clone()))
+            }
+            (
+                Some(RustCrateSource::GithubBranch {
+                    owner,
+                    repo,
+                    branch,
+                }),
+                CrateSource::Git(git),
+            ) if git.is_github_repo(owner, repo) => {
+                let gh = GitHubRepo::new(owner, repo, cache);
+                let same_branch = match &git.reference {
+                    GitReference::Branch(name) => name == branch,
+                    GitReference::DefaultBranch => gh.get_info()?.default_branch == *branch,
+                    GitReference::Rev(_) => git.is_pinned(),
+                    GitReference::Tag(_) => false,
+                };
+                if same_branch {
+                    Ok(gh.get_commit(&git.commit)?.into())
+                } else {
+                    Ok(PackageVersion::None)
+                }
+            }
+            (Some(RustCrateSource::GithubTag { owner, repo, tag }), CrateSource::Git(git))
+                if git.is_github_repo(owner, repo)
+                    && (git.reference == GitReference::Tag(tag.clone()) || git.is_pinned()) =>
+            {
+                Ok(GitHubRepo::new(owner, repo, cache)
+                    .get_commit(&git.commit)?
+                    .into())
+            }
+            (
+                Some(RustCrateSource::GithubRevision {
+                    owner,
+                    repo,
+                    revision,
+                }),
+                CrateSource::Git(git),
+            ) if git.is_github_repo(owner, repo)
+                && (git.reference == GitReference::Rev(revision.clone()) || git.is_pinned()) =>
+            {
+                Ok(GitHubRepo::new(owner, repo, cache)
+                    .get_commit(&git.commit)?
+                    .into())
+            }
+            (Some(source), CrateSource::Git(git)) if git.is_repo(&source.git_url()) => {
+                let Some((host, branch)) = GitHost::from_crate_source(source, cache) else {
+                    return Ok(PackageVersion::None);
+                };
+                let same_branch = match (&git.reference, branch) {
+                    (GitReference::Branch(name), Some(branch)) => name == branch,
+                    (GitReference::Branch(name), None) => *name == host.default_branch()?,
+                    (GitReference::DefaultBranch, Some(branch)) => host.default_branch()? == branch,
+                    (GitReference::DefaultBranch, None) => true,
+                    (GitReference::Rev(_), _) => git.is_pinned(),
+                    (GitReference::Tag(_), _) => false,
+                };
+                if same_branch {
+                    host.commit(&git.commit)
+                } else {
+                    Ok(PackageVersion::None)
+                }
+            }
+            _ => Ok(PackageVersion::None),
+        }
     }
 
     pub fn refresh_state(&mut self) -> Result<(), Error> {
-        *self = Self::init()?;
+        *self = Self::init(&self.cargo_home)?;
         Ok(())
     }
 
@@ -117,7 +724,23 @@ impl CargoState {
         &self,
         spec: &CrateSpec,
         cache: &Cache,
-    ) -> Result<PackageVersion, Error> {
+    ) -> Result<LatestVersion, Error> {
+        if let (None, Some(req)) = (spec.source, &spec.version_req) {
+            let versions = CratesIoCrate::new(&spec.name.0, cache).get_versions()?;
+            return Ok(LatestVersion::select(Some(req), versions));
+        }
+
+        cache
+            .get_or_insert(
+                &format!("cargo/{}.latest", spec.cache_name()),
+                1.hour(),
+                || Self::search_latest_version(spec, cache),
+            )
+            .map(LatestVersion::unconstrained)
+    }
+
+    /// Look up the latest version of the crate ignoring any version requirement
+    fn search_latest_version(spec: &CrateSpec, cache: &Cache) -> Result<PackageVersion, Error> {
         match spec.source {
             Some(RustCrateSource::GithubBranch {
                 owner,
@@ -126,12 +749,32 @@ impl CargoState {
             }) => {
                 let info = GitHubRepo::new(owner, repo, cache).get_branch_info(branch)?;
 
-                return Ok(PackageVersion::Commit {
-                    id: info.commit.sha,
-                    date: info.commit.commit.author.date,
-                });
+                return Ok(info.commit.into());
+            }
+            Some(RustCrateSource::GithubTag { owner, repo, tag }) => {
+                return Ok(GitHubRepo::new(owner, repo, cache)
+                    .get_commit(&format!("refs/tags/{tag}"))?
+                    .into());
+            }
+            Some(RustCrateSource::GithubRevision {
+                owner,
+                repo,
+                revision,
+            }) => {
+                return Ok(GitHubRepo::new(owner, repo, cache)
+                    .get_commit(revision)?
+                    .into());
+            }
+            Some(
+                source @ (RustCrateSource::GitLabBranch { .. }
+                | RustCrateSource::GiteaBranch { .. }
+                | RustCrateSource::CodebergBranch { .. }
+                | RustCrateSource::GitBranch { .. }),
+            ) => {
+                if let Some((host, branch)) = GitHost::from_crate_source(source, cache) {
+                    return host.branch_commit(branch);
+                }
             }
-            Some(unk) => todo!("{unk:#?}"),
             None => {
                 let res = Command::new("cargo")
                     .arg("search")
@@ -173,39 +816,219 @@ impl CargoState {
         Ok(PackageVersion::None)
     }
 
-    pub fn install_package(&mut self, spec: &CrateSpec) -> Result<PackageVersion, Error> {
-        let mut cmd = Command::new("cargo");
+    /// Check if `cargo binstall` is installed, only checked once
+    fn binstall_available(&self) -> bool {
+        *self.binstall_available.get_or_init(|| {
+            let available = Command::new("cargo")
+                .args(["binstall", "-V"])
+                .output()
+                .is_ok_and(|res| res.status.success());
+            if !available {
+                log::info!("cargo binstall is not available, falling back to cargo install");
+            }
+            available
+        })
+    }
 
-        cmd.arg("install");
+    /// Install the crate, `version` is installed exactly unless it is [PackageVersion::None]
+    pub fn install_package(
+        &mut self,
+        spec: &CrateSpec,
+        version: &PackageVersion,
+        cache: &Cache,
+    ) -> Result<PackageVersion, Error> {
+        let binstall_available =
+            spec.installer == CargoInstaller::Binstall && self.binstall_available();
+        let (what, args) = spec.install_args(version, binstall_available);
 
-        match spec.source {
-            Some(RustCrateSource::GithubBranch {
-                owner,
-                repo,
-                branch,
-            }) => {
-                cmd.args(["--git", &format!("https://github.com/{owner}/{repo}.git")])
-                    .args(["--branch", branch]);
+        run_status(what, Command::new("cargo").args(args))?;
+
+        self.refresh_state()?;
+
+        self.package_version(spec, cache)
+    }
+
+    /// Check if a crate with the specified name is installed, regardless of its source
+    pub fn is_crate_installed(&self, crate_name: &str) -> bool {
+        self.crates.contains_key(&CrateName::new(crate_name))
+    }
+
+    pub fn uninstall_package(&mut self, crate_name: &str) -> Result<(), Error> {
+        run_status(
+            "cargo uninstall",
+            Command::new("cargo").arg("uninstall").arg(crate_name),
+        )?;
+
+        self.refresh_state()
+    }
+}
+
+impl PackageProvider for CargoState {
+    fn handles(&self, package: &Package) -> bool {
+        matches!(package, Package::RustCrate { .. })
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::RustCrate { .. })
+    }
+
+    fn installed_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        self.package_version(&CrateSpec::from_package(package)?, ctx.cache())
+    }
+
+    fn latest_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        self.latest_package_version(&CrateSpec::from_package(package)?, ctx.cache())
+    }
+
+    fn warnings(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<Vec<PackageWarning>, Error> {
+        let spec = CrateSpec::from_package(package)?;
+        let mut warnings = Vec::new();
+
+        if self.package_version(&spec, ctx.cache())? != PackageVersion::None {
+            if let Some(build) = self
+                .installed_crate(&spec)
+                .and_then(|info| info.build.as_ref())
+            {
+                let declared = spec.declared_features();
+                if build.features != declared {
+                    warnings.push(PackageWarning::FeaturesDiffer {
+                        installed: build.features.clone(),
+                        declared,
+                    });
+                }
             }
-            Some(unk) => todo!("{unk:#?}"),
-            None => {}
         }
 
-        cmd.arg(&spec.name.0)
-            .status()
-            .map_err(|e| e.to_string())
-            .and_then(|res| {
-                if res.success() {
-                    Ok(())
-                } else {
-                    Err(format!("returned status {res}"))
-                }
-            })
-            .map_err(|e| Error::ShellCommandFailed("cargo install", e))?;
+        Ok(warnings)
+    }
 
-        self.refresh_state()?;
+    /// Commits of crates from GitHub are ordered by their history
+    fn compare_versions(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+        a: &PackageVersion,
+        b: &PackageVersion,
+    ) -> Result<VersionOrder, Error> {
+        match CrateSpec::from_package(package)?.source {
+            Some(
+                RustCrateSource::GithubBranch { owner, repo, .. }
+                | RustCrateSource::GithubTag { owner, repo, .. }
+                | RustCrateSource::GithubRevision { owner, repo, .. },
+            ) => GitHubRepo::new(owner, repo, ctx.cache()).compare_versions(a, b),
+            _ => Ok(a.compare(b)),
+        }
+    }
+
+    /// Only known for crates from GitHub
+    fn changes(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+        from: &PackageVersion,
+        to: &PackageVersion,
+    ) -> Result<Option<PackageChanges>, Error> {
+        match CrateSpec::from_package(package)?.source {
+            Some(
+                RustCrateSource::GithubBranch { owner, repo, .. }
+                | RustCrateSource::GithubTag { owner, repo, .. }
+                | RustCrateSource::GithubRevision { owner, repo, .. },
+            ) => GitHubRepo::new(owner, repo, ctx.cache()).changes(from, to, None),
+            _ => Ok(None),
+        }
+    }
+
+    fn details(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageDetails, Error> {
+        let spec = CrateSpec::from_package(package)?;
+        let bin_dir = self.cargo_home.join("bin");
+        let environment = match package.package() {
+            Package::RustCrate { environment, .. } => environment
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(PackageDetails {
+            source: Some(
+                spec.source
+                    .map_or_else(|| "crates.io".into(), |v| v.describe()),
+            ),
+            bins: self
+                .installed_crate(&spec)
+                .iter()
+                .flat_map(|info| &info.bins)
+                .map(|bin| bin_dir.join(bin))
+                .collect(),
+            location: Some(bin_dir),
+            environment,
+        })
+    }
+
+    fn install(
+        &mut self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        self.install_package(&CrateSpec::from_package(package)?, version, ctx.cache())
+    }
+
+    fn installed_kind(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        Ok(InstalledKind::RustCrate {
+            crate_name: CrateSpec::from_package(package)?.crate_name().into(),
+        })
+    }
+
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        match kind {
+            InstalledKind::RustCrate { crate_name } => self.is_crate_installed(crate_name),
+            _ => false,
+        }
+    }
+
+    fn uninstall(&mut self, _ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        match kind {
+            InstalledKind::RustCrate { crate_name } => self.uninstall_package(crate_name),
+            _ => Ok(()),
+        }
+    }
+
+    /// Lists all the crates installed using `cargo install`, not only the ones installed by logix
+    fn list_installed(&self, _ctx: &ProviderContext) -> Result<Vec<InstalledPackage>, Error> {
+        Ok(self
+            .installed_crates()
+            .map(|info| InstalledPackage {
+                name: info.name.clone(),
+                kind: InstalledKind::RustCrate {
+                    crate_name: info.name.clone(),
+                },
+                version: PackageVersion::Semver(info.version.clone()),
+            })
+            .collect())
+    }
 
-        Ok(self.package_version(spec))
+    fn refresh(&mut self) -> Result<(), Error> {
+        self.refresh_state()
     }
 }
 
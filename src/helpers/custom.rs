@@ -0,0 +1,223 @@
+use std::{path::Path, process::Command};
+
+use crate::{
+    config::{self, Package, Source},
+    error::Error,
+    git_host::GitHost,
+    github::GitHubRepo,
+    helpers::git::GitCheckout,
+    installed_packages::InstalledKind,
+    managed_package::{
+        LatestVersion, ManagedPackage, PackageChanges, PackageDetails, PackageVersion, VersionOrder,
+    },
+    package_provider::{PackageProvider, ProviderContext},
+};
+
+use super::run_status;
+
+/// Run a build or install `step` of a custom package in the directory of its sources
+pub fn run_step(
+    package: &str,
+    step: &'static str,
+    cmd: &config::Command,
+    dir: &Path,
+) -> Result<(), Error> {
+    let config::Command::ShellCommand { command } = cmd;
+
+    log::debug!("Running the {step} step of {package:?} in {dir:?}: {command}");
+
+    run_status(
+        step,
+        Command::new("sh").arg("-c").arg(command).current_dir(dir),
+    )
+    .map_err(|e| match e {
+        Error::ShellCommandFailed(_, e) => Error::CustomPackageStep(package.into(), step, e),
+        e => e,
+    })
+}
+
+/// The packages built from source using the commands from the config
+pub struct CustomPackages;
+
+impl CustomPackages {
+    fn source<'a>(package: &ManagedPackage<'a>) -> Result<&'a Source, Error> {
+        match package.package() {
+            Package::Custom { source, .. } => Ok(source),
+            _ => Err(Error::UnsupportedPackage(package.name().into(), "custom")),
+        }
+    }
+
+    /// The branch declared for the source, `None` for the default branch
+    fn branch(source: &Source) -> Option<&str> {
+        match source {
+            Source::Git { url: _, branch } => branch.as_deref(),
+            Source::GitHub { .. }
+            | Source::GitLab { .. }
+            | Source::Gitea { .. }
+            | Source::Codeberg { .. } => None,
+        }
+    }
+
+    fn checkout(ctx: &ProviderContext, package: &ManagedPackage) -> Result<GitCheckout, Error> {
+        match package.package() {
+            Package::Custom { local_dir, .. } => Ok(GitCheckout::new(
+                ctx.custom_package_dir(package.name(), local_dir.as_ref()),
+            )),
+            _ => Err(Error::UnsupportedPackage(package.name().into(), "custom")),
+        }
+    }
+}
+
+impl PackageProvider for CustomPackages {
+    fn handles(&self, package: &Package) -> bool {
+        matches!(package, Package::Custom { .. })
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::Custom { .. })
+    }
+
+    /// The version recorded when it was installed, the installed files are unknown
+    fn installed_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        Ok(ctx
+            .installed_package(package.name(), &self.installed_kind(ctx, package)?)
+            .map_or(PackageVersion::None, |v| v.version.clone()))
+    }
+
+    fn latest_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        let source = Self::source(package)?;
+        Ok(LatestVersion::unconstrained(
+            GitHost::from_source(source, ctx.cache()).branch_commit(Self::branch(source))?,
+        ))
+    }
+
+    fn downloaded_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        Self::checkout(ctx, package)?.head_commit()
+    }
+
+    /// Commits from GitHub are ordered by their history
+    fn compare_versions(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+        a: &PackageVersion,
+        b: &PackageVersion,
+    ) -> Result<VersionOrder, Error> {
+        match Self::source(package)? {
+            Source::GitHub { owner, repo } => {
+                GitHubRepo::new(owner, repo, ctx.cache()).compare_versions(a, b)
+            }
+            Source::GitLab { .. }
+            | Source::Gitea { .. }
+            | Source::Codeberg { .. }
+            | Source::Git { .. } => Ok(a.compare(b)),
+        }
+    }
+
+    /// Only known for sources on GitHub
+    fn changes(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+        from: &PackageVersion,
+        to: &PackageVersion,
+    ) -> Result<Option<PackageChanges>, Error> {
+        match Self::source(package)? {
+            Source::GitHub { owner, repo } => {
+                GitHubRepo::new(owner, repo, ctx.cache()).changes(from, to, None)
+            }
+            Source::GitLab { .. }
+            | Source::Gitea { .. }
+            | Source::Codeberg { .. }
+            | Source::Git { .. } => Ok(None),
+        }
+    }
+
+    /// The installed binaries are unknown as the install step is a custom command
+    fn details(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageDetails, Error> {
+        Ok(PackageDetails {
+            source: Some(Self::source(package)?.describe()),
+            location: Some(Self::checkout(ctx, package)?.dir().to_path_buf()),
+            ..Default::default()
+        })
+    }
+
+    fn install(
+        &mut self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        let Package::Custom {
+            source,
+            build,
+            install,
+            ..
+        } = package.package()
+        else {
+            return Err(Error::UnsupportedPackage(package.name().into(), "custom"));
+        };
+
+        let checkout = Self::checkout(ctx, package)?;
+        checkout.clone_or_fetch(&source.git_url())?;
+        checkout.checkout(&match version {
+            PackageVersion::Commit { id, .. } => id.clone(),
+            PackageVersion::None | PackageVersion::Semver(_) | PackageVersion::Other(_) => {
+                Self::branch(source).map_or_else(|| "origin/HEAD".into(), |v| format!("origin/{v}"))
+            }
+        })?;
+
+        if let Some(cmd) = build {
+            run_step(package.name(), "build", cmd, checkout.dir())?;
+        }
+        if let Some(cmd) = install {
+            run_step(package.name(), "install", cmd, checkout.dir())?;
+        }
+
+        // NOTE: Record the commit that was built, not the requested version
+        checkout.head_commit()
+    }
+
+    fn installed_kind(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        Ok(InstalledKind::Custom {
+            local_dir: Self::checkout(ctx, package)?.dir().to_path_buf(),
+        })
+    }
+
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        match kind {
+            InstalledKind::Custom { local_dir } => local_dir.exists(),
+            _ => false,
+        }
+    }
+
+    /// There is no uninstall step, so the record is kept until the sources are removed
+    fn uninstall(&mut self, _ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        match kind {
+            InstalledKind::Custom { local_dir } => {
+                Err(Error::CustomPackageUninstall(local_dir.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use super::run_command;
+use crate::{error::Error, managed_package::PackageVersion};
+
+/// A local clone of a git repository
+pub struct GitCheckout {
+    dir: PathBuf,
+}
+
+impl GitCheckout {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Check if the directory contains a git repository
+    pub fn exists(&self) -> bool {
+        self.dir.join(".git").exists()
+    }
+
+    fn git(&self) -> Command {
+        let mut cmd = Command::new("git");
+        cmd.arg("-C").arg(&self.dir);
+        cmd
+    }
+
+    /// Clone the repository if it doesn't exist, otherwise fetch the latest changes
+    pub fn clone_or_fetch(&self, url: &str) -> Result<(), Error> {
+        if self.exists() {
+            run_command("git fetch", self.git().args(["fetch", "--tags", "origin"]))?;
+        } else {
+            if let Some(parent) = self.dir.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| Error::ShellCommandFailed("git clone", e.to_string()))?;
+            }
+            run_command(
+                "git clone",
+                Command::new("git").args(["clone", url]).arg(&self.dir),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Check out the specified commit, leaving the repository in a detached state
+    pub fn checkout(&self, commit: &str) -> Result<(), Error> {
+        run_command(
+            "git checkout",
+            self.git().args(["checkout", "--quiet", "--detach", commit]),
+        )?;
+        Ok(())
+    }
+
+    /// Returns the commit that is checked out, or [PackageVersion::None] if
+    /// the repository hasn't been cloned yet
+    pub fn head_commit(&self) -> Result<PackageVersion, Error> {
+        if !self.exists() {
+            return Ok(PackageVersion::None);
+        }
+
+        // NOTE: The author date is used as that is what GitHub reports for commits
+        let out = run_command("git log", self.git().args(["log", "-1", "--format=%H %aI"]))?;
+        let parsed = out.trim().split_once(' ').and_then(|(id, date)| {
+            Some(PackageVersion::Commit {
+                id: id.into(),
+                date: Some(date.parse().ok()?),
+            })
+        });
+
+        parsed.ok_or_else(|| {
+            Error::ShellCommandFailed("git log", format!("unexpected output {out:?}"))
+        })
+    }
+}
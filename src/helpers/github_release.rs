@@ -0,0 +1,401 @@
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::{
+    cache::Cache,
+    config::Package,
+    error::Error,
+    file_index::ContentHash,
+    github::{GitHubAsset, GitHubRelease, GitHubRepo},
+    helpers::archive::{self, ArchiveKind},
+    installed_packages::InstalledKind,
+    managed_package::{
+        parse_version_req, LatestVersion, ManagedPackage, PackageChanges, PackageDetails,
+        PackageVersion,
+    },
+    package_provider::{PackageProvider, ProviderContext},
+};
+
+/// Names commonly used in release assets for the host OS
+fn os_names() -> Vec<&'static str> {
+    match std::env::consts::OS {
+        "macos" => vec!["darwin", "apple", "macos", "osx"],
+        "windows" => vec!["windows", "win64"],
+        os => vec![os],
+    }
+}
+
+/// Names commonly used in release assets for the host architecture
+fn arch_names() -> Vec<&'static str> {
+    match std::env::consts::ARCH {
+        "x86_64" => vec!["x86_64", "amd64", "x64"],
+        "aarch64" => vec!["aarch64", "arm64"],
+        arch => vec![arch],
+    }
+}
+
+/// Returns true if `name` is commonly used for an asset with the SHA256 checksum
+/// of `asset`, either of only that asset or of all assets in the release
+fn is_checksum_name(name: &str, asset: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let asset = asset.to_ascii_lowercase();
+    name == format!("{asset}.sha256")
+        || name == format!("{asset}.sha256sum")
+        || ["sha256sums", "sha256sums.txt", "checksums.txt"].contains(&name.as_str())
+        || name.ends_with("_checksums.txt")
+        || name.ends_with("-checksums.txt")
+}
+
+fn alternatives(names: &[&str]) -> String {
+    let names = names.iter().map(|v| regex::escape(v)).collect::<Vec<_>>();
+    format!("(?:{})", names.join("|"))
+}
+
+/// Describes how to install a binary from the releases of a GitHub repository
+pub struct ReleaseSpec<'a> {
+    package: &'a str,
+    owner: &'a str,
+    repo: &'a str,
+    asset_pattern: Option<Regex>,
+    bin: &'a str,
+    checksum_asset: Option<&'a str>,
+    version_req: Option<semver::VersionReq>,
+}
+
+impl<'a> ReleaseSpec<'a> {
+    pub fn new(
+        package: &'a str,
+        owner: &'a str,
+        repo: &'a str,
+        asset_pattern: Option<&str>,
+        bin: Option<&'a str>,
+        checksum_asset: Option<&'a str>,
+        version_req: Option<&str>,
+    ) -> Result<Self, Error> {
+        let asset_pattern = asset_pattern
+            .map(|pattern| {
+                let expanded = pattern
+                    .replace("{os}", &alternatives(&os_names()))
+                    .replace("{arch}", &alternatives(&arch_names()));
+                Regex::new(&format!("(?i)^(?:{expanded})$")).map_err(|e| {
+                    Error::InvalidAssetPattern(package.into(), pattern.into(), e.to_string())
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            package,
+            owner,
+            repo,
+            asset_pattern,
+            bin: bin.unwrap_or(package),
+            checksum_asset,
+            version_req: parse_version_req(package, version_req)?,
+        })
+    }
+
+    fn from_package(package: &'a ManagedPackage) -> Result<Self, Error> {
+        let Package::GitHubRelease {
+            owner,
+            repo,
+            asset_pattern,
+            bin,
+            checksum_asset,
+            version,
+            ..
+        } = package.package()
+        else {
+            return Err(Error::UnsupportedPackage(
+                package.name().into(),
+                "GitHub release",
+            ));
+        };
+        Self::new(
+            package.name(),
+            owner,
+            repo,
+            asset_pattern.as_deref(),
+            bin.as_deref(),
+            checksum_asset.as_deref(),
+            version.as_deref(),
+        )
+    }
+
+    /// The name of the installed binary
+    pub fn bin(&self) -> &str {
+        self.bin
+    }
+
+    fn repo(&self, cache: &Cache) -> GitHubRepo {
+        GitHubRepo::new(self.owner, self.repo, cache)
+    }
+
+    /// Pick the asset to download for the host
+    fn select_asset<'r>(&self, release: &'r GitHubRelease) -> Option<&'r GitHubAsset> {
+        if let Some(pattern) = &self.asset_pattern {
+            return release.assets.iter().find(|v| pattern.is_match(&v.name));
+        }
+
+        let os_names = os_names();
+        let arch_names = arch_names();
+
+        let mut candidates = release
+            .assets
+            .iter()
+            .filter(|asset| {
+                let name = asset.name.to_ascii_lowercase();
+                let is_binary =
+                    ArchiveKind::from_name(&name) != ArchiveKind::Plain || !name.contains('.');
+                is_binary
+                    && os_names.iter().any(|v| name.contains(v))
+                    && arch_names.iter().any(|v| name.contains(v))
+            })
+            .collect::<Vec<_>>();
+
+        // NOTE: Statically linked binaries works on more systems
+        candidates.sort_by_key(|asset| !asset.name.contains("musl"));
+        candidates.first().copied()
+    }
+
+    /// Find the asset with the checksum of `asset`, either the configured one or
+    /// one of the names commonly used for checksums
+    fn checksum_asset<'r>(
+        &self,
+        release: &'r GitHubRelease,
+        asset: &GitHubAsset,
+    ) -> Result<&'r GitHubAsset, Error> {
+        if let Some(checksum_asset) = self.checksum_asset {
+            let checksum_name = checksum_asset.replace("{asset}", &asset.name);
+            return release
+                .assets
+                .iter()
+                .find(|v| v.name == checksum_name)
+                .ok_or_else(|| Error::ChecksumNotFound(asset.name.clone(), checksum_name));
+        }
+
+        release
+            .assets
+            .iter()
+            .find(|v| is_checksum_name(&v.name, &asset.name))
+            .ok_or_else(|| Error::NoChecksumAsset(self.package.into(), asset.name.clone()))
+    }
+
+    /// Verify the downloaded asset using the published checksum
+    fn verify_checksum(
+        &self,
+        release: &GitHubRelease,
+        asset: &GitHubAsset,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let checksum_asset = self.checksum_asset(release, asset)?;
+        let not_found = || Error::ChecksumNotFound(asset.name.clone(), checksum_asset.name.clone());
+        let checksums = checksum_asset.download()?;
+
+        // NOTE: Either a file with only the checksum, or the `sha256sum` format with one file per line
+        let checksums = String::from_utf8_lossy(&checksums);
+        let expected = checksums
+            .lines()
+            .find_map(|line| {
+                let mut it = line.split_whitespace();
+                let hash = it.next()?;
+                match it.next() {
+                    None => Some(hash),
+                    Some(file) => Path::new(file.trim_start_matches('*'))
+                        .file_name()
+                        .is_some_and(|v| *v == *asset.name)
+                        .then_some(hash),
+                }
+            })
+            .ok_or_else(not_found)?;
+
+        let actual = ContentHash::of_bytes(data);
+        if expected.eq_ignore_ascii_case(actual.as_hex()) {
+            Ok(())
+        } else {
+            Err(Error::ChecksumMismatch(
+                asset.name.clone(),
+                expected.into(),
+                actual.as_hex().into(),
+            ))
+        }
+    }
+
+    /// Find the newest releases, pre-releases are ignored
+    pub fn latest_version(&self, cache: &Cache) -> Result<LatestVersion, Error> {
+        let versions = self
+            .repo(cache)
+            .get_releases()?
+            .into_iter()
+            .filter(|release| !release.prerelease)
+            .filter_map(|release| {
+                let version = release.version();
+                if version.is_none() {
+                    log::debug!(
+                        "Ignoring release {:?} of {:?} as it isn't a semver version",
+                        release.tag_name,
+                        self.package
+                    );
+                }
+                version
+            });
+
+        Ok(LatestVersion::select(self.version_req.as_ref(), versions))
+    }
+
+    /// Download the release with the specified version and install the binary
+    /// into `bin_dir`, returns the path of the installed binary
+    pub fn install(
+        &self,
+        cache: &Cache,
+        version: &PackageVersion,
+        bin_dir: &Path,
+    ) -> Result<PathBuf, Error> {
+        let PackageVersion::Semver(version) = version else {
+            return Err(Error::ReleaseNotFound(
+                self.package.into(),
+                version.to_string(),
+            ));
+        };
+
+        let releases = self.repo(cache).get_releases()?;
+        let release = releases
+            .iter()
+            .find(|release| release.version().as_ref() == Some(version))
+            .ok_or_else(|| Error::ReleaseNotFound(self.package.into(), version.to_string()))?;
+
+        let asset = self
+            .select_asset(release)
+            .ok_or_else(|| Error::NoMatchingAsset(self.package.into(), release.tag_name.clone()))?;
+
+        log::info!(
+            "Downloading {:?} for package {:?}",
+            asset.name,
+            self.package
+        );
+
+        let data = asset.download()?;
+        self.verify_checksum(release, asset, &data)?;
+
+        let bin = archive::extract_file(&asset.name, &data, self.bin)?;
+        let path = bin_dir.join(self.bin);
+        install_binary(&path, &bin)?;
+        Ok(path)
+    }
+}
+
+/// The binaries downloaded from GitHub releases into the bin directory of the user
+pub struct GitHubReleases;
+
+impl PackageProvider for GitHubReleases {
+    fn handles(&self, package: &Package) -> bool {
+        matches!(package, Package::GitHubRelease { .. })
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::GitHubRelease { .. })
+    }
+
+    /// The version recorded when it was installed, as long as the binary is still there
+    fn installed_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        let kind = self.installed_kind(ctx, package)?;
+        Ok(ctx
+            .installed_package(package.name(), &kind)
+            .filter(|_| self.is_installed(ctx, &kind))
+            .map_or(PackageVersion::None, |v| v.version.clone()))
+    }
+
+    fn latest_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        ReleaseSpec::from_package(package)?.latest_version(ctx.cache())
+    }
+
+    fn changes(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+        from: &PackageVersion,
+        to: &PackageVersion,
+    ) -> Result<Option<PackageChanges>, Error> {
+        let spec = ReleaseSpec::from_package(package)?;
+        spec.repo(ctx.cache())
+            .changes(from, to, spec.version_req.as_ref())
+    }
+
+    fn details(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageDetails, Error> {
+        let spec = ReleaseSpec::from_package(package)?;
+        Ok(PackageDetails {
+            source: Some(format!(
+                "https://github.com/{}/{} releases",
+                spec.owner, spec.repo
+            )),
+            location: Some(ctx.user_bin().as_path().to_path_buf()),
+            bins: vec![ctx.user_bin_path(spec.bin())],
+            environment: Vec::new(),
+        })
+    }
+
+    fn install(
+        &mut self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        ReleaseSpec::from_package(package)?.install(ctx.cache(), version, ctx.user_bin())?;
+        Ok(version.clone())
+    }
+
+    fn installed_kind(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        Ok(InstalledKind::GitHubRelease {
+            bin: ctx.user_bin_path(ReleaseSpec::from_package(package)?.bin()),
+        })
+    }
+
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        match kind {
+            InstalledKind::GitHubRelease { bin } => bin.exists(),
+            _ => false,
+        }
+    }
+
+    fn uninstall(&mut self, _ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        match kind {
+            InstalledKind::GitHubRelease { bin } => std::fs::remove_file(bin)
+                .map_err(|e| Error::InstallBinary(bin.clone(), e.to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Atomically replace the binary at `path`
+fn install_binary(path: &Path, data: &[u8]) -> Result<(), Error> {
+    let tmp_path = path.with_extension("logix-tmp");
+
+    let res = std::fs::create_dir_all(path.parent().unwrap())
+        .and_then(|()| std::fs::write(&tmp_path, data))
+        .and_then(|()| {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o755))?;
+            }
+            std::fs::rename(&tmp_path, path)
+        });
+
+    res.map_err(|e| Error::InstallBinary(path.into(), e.to_string()))
+}
@@ -0,0 +1,253 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use super::{run_command, run_status};
+use crate::{
+    cache::Cache,
+    config::Package,
+    error::Error,
+    go_proxy::GoModule,
+    installed_packages::InstalledKind,
+    managed_package::{
+        parse_loose_version, parse_version_req, LatestVersion, ManagedPackage, PackageDetails,
+        PackageVersion,
+    },
+    package_provider::{PackageProvider, ProviderContext},
+};
+
+/// The name of the binary installed by `go install`, the last component of
+/// the package path not counting a major version suffix such as `/v2`
+fn bin_name(package_path: &str) -> &str {
+    let mut parts = package_path.rsplit('/');
+    let last = parts.next().unwrap_or(package_path);
+    let is_major_suffix = last
+        .strip_prefix('v')
+        .is_some_and(|v| !v.is_empty() && v.bytes().all(|c| c.is_ascii_digit()));
+    match parts.next() {
+        Some(prev) if is_major_suffix => prev,
+        _ => last,
+    }
+}
+
+pub struct GoPackageSpec<'a> {
+    package_path: &'a str,
+    module: &'a str,
+    version_req: Option<semver::VersionReq>,
+}
+
+impl<'a> GoPackageSpec<'a> {
+    pub fn new(
+        name: &str,
+        package_path: &'a str,
+        module: Option<&'a str>,
+        version_req: Option<&str>,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            package_path,
+            module: module.unwrap_or(package_path),
+            version_req: parse_version_req(name, version_req)?,
+        })
+    }
+
+    fn from_package(package: &'a ManagedPackage) -> Result<Self, Error> {
+        let Package::GoPackage {
+            package_path,
+            module,
+            version,
+            ..
+        } = package.package()
+        else {
+            return Err(Error::UnsupportedPackage(package.name().into(), "go"));
+        };
+        Self::new(
+            package.name(),
+            package_path,
+            module.as_deref(),
+            version.as_deref(),
+        )
+    }
+}
+
+/// The go programs installed using `go install` into the go bin directory
+pub struct GoState {
+    bin_dir: PathBuf,
+}
+
+impl GoState {
+    pub fn new(bin_dir: &Path) -> Self {
+        Self {
+            bin_dir: bin_dir.into(),
+        }
+    }
+
+    /// The path of the binary installed from the package
+    pub fn bin_path(&self, package_path: &str) -> PathBuf {
+        self.bin_dir.join(bin_name(package_path))
+    }
+
+    /// Returns the installed version using the build info embedded in the binary,
+    /// [PackageVersion::None] if the binary is missing or built from another package
+    pub fn package_version(&self, spec: &GoPackageSpec) -> Result<PackageVersion, Error> {
+        let bin = self.bin_path(spec.package_path);
+        if !bin.exists() {
+            return Ok(PackageVersion::None);
+        }
+
+        let out = run_command(
+            "go version -m",
+            Command::new("go").args(["version", "-m"]).arg(&bin),
+        )?;
+
+        match Self::parse_build_info(&out) {
+            Some((path, version)) if path == spec.package_path => Ok(version),
+            info => {
+                log::debug!(
+                    "The binary {bin:?} was built from {:?}, not {:?}",
+                    info.map(|(path, _)| path),
+                    spec.package_path
+                );
+                Ok(PackageVersion::None)
+            }
+        }
+    }
+
+    /// Parse the build info printed by `go version -m`, returns the package path
+    /// and the version of the main module
+    pub fn parse_build_info(out: &str) -> Option<(&str, PackageVersion)> {
+        let mut path = None;
+        let mut version = None;
+        for line in out.lines() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("path") => path = parts.next(),
+                Some("mod") => version = parts.nth(1),
+                _ => {}
+            }
+        }
+
+        // NOTE: Binaries built from a local checkout has the version `(devel)`
+        Some((
+            path?,
+            version
+                .and_then(parse_loose_version)
+                .map_or(PackageVersion::None, PackageVersion::Semver),
+        ))
+    }
+
+    pub fn latest_package_version(
+        &self,
+        spec: &GoPackageSpec,
+        cache: &Cache,
+    ) -> Result<LatestVersion, Error> {
+        let module = GoModule::new(spec.module, cache);
+        let versions = module.get_versions()?;
+
+        if versions.is_empty() {
+            // NOTE: Modules without tags only have a pseudo-version such as `v0.0.0-20240101000000-abcdef123456`
+            let latest = parse_loose_version(&module.get_latest()?.version)
+                .map_or(PackageVersion::None, PackageVersion::Semver);
+            return Ok(LatestVersion::unconstrained(latest));
+        }
+
+        Ok(LatestVersion::select(spec.version_req.as_ref(), versions))
+    }
+
+    /// Install the package, `version` is installed exactly unless it is [PackageVersion::None]
+    pub fn install_package(
+        &self,
+        spec: &GoPackageSpec,
+        version: &PackageVersion,
+    ) -> Result<(), Error> {
+        let version = match version {
+            PackageVersion::Semver(version) => format!("v{version}"),
+            PackageVersion::None | PackageVersion::Commit { .. } | PackageVersion::Other(_) => {
+                "latest".into()
+            }
+        };
+        run_status(
+            "go install",
+            Command::new("go")
+                .arg("install")
+                .arg(format!("{}@{version}", spec.package_path))
+                .env("GOBIN", &self.bin_dir),
+        )
+    }
+}
+
+impl PackageProvider for GoState {
+    fn handles(&self, package: &Package) -> bool {
+        matches!(package, Package::GoPackage { .. })
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::Go { .. })
+    }
+
+    fn installed_version(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        self.package_version(&GoPackageSpec::from_package(package)?)
+    }
+
+    fn latest_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        self.latest_package_version(&GoPackageSpec::from_package(package)?, ctx.cache())
+    }
+
+    fn details(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageDetails, Error> {
+        let spec = GoPackageSpec::from_package(package)?;
+        Ok(PackageDetails {
+            source: Some(format!("go module {}", spec.module)),
+            location: Some(self.bin_dir.clone()),
+            bins: vec![self.bin_path(spec.package_path)],
+            environment: Vec::new(),
+        })
+    }
+
+    fn install(
+        &mut self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        let spec = GoPackageSpec::from_package(package)?;
+        self.install_package(&spec, version)?;
+        self.package_version(&spec)
+    }
+
+    fn installed_kind(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        Ok(InstalledKind::Go {
+            bin: self.bin_path(GoPackageSpec::from_package(package)?.package_path),
+        })
+    }
+
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        match kind {
+            InstalledKind::Go { bin } => bin.exists(),
+            _ => false,
+        }
+    }
+
+    fn uninstall(&mut self, _ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        match kind {
+            InstalledKind::Go { bin } => std::fs::remove_file(bin)
+                .map_err(|e| Error::InstallBinary(bin.clone(), e.to_string())),
+            _ => Ok(()),
+        }
+    }
+}
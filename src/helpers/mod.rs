@@ -1 +1,49 @@
+use std::process::Command;
+
+use crate::error::Error;
+
+pub mod archive;
 pub mod cargo;
+pub mod custom;
+pub mod git;
+pub mod github_release;
+pub mod go;
+pub mod npm;
+pub mod plugin;
+pub mod python;
+pub mod system;
+pub mod url_archive;
+
+/// Run the command and return its output, `what` describes the command in errors
+pub(crate) fn run_command(what: &'static str, cmd: &mut Command) -> Result<String, Error> {
+    log::debug!("Running {cmd:?}");
+    cmd.output()
+        .map_err(|e| e.to_string())
+        .and_then(|res| {
+            if res.status.success() {
+                Ok(String::from_utf8_lossy(&res.stdout).into_owned())
+            } else {
+                Err(format!(
+                    "returned status {}: {}",
+                    res.status,
+                    String::from_utf8_lossy(&res.stderr).trim()
+                ))
+            }
+        })
+        .map_err(|e| Error::ShellCommandFailed(what, e))
+}
+
+/// Run the command with the output shown to the user, used for installing packages
+pub(crate) fn run_status(what: &'static str, cmd: &mut Command) -> Result<(), Error> {
+    log::debug!("Running {cmd:?}");
+    cmd.status()
+        .map_err(|e| e.to_string())
+        .and_then(|res| {
+            if res.success() {
+                Ok(())
+            } else {
+                Err(format!("returned status {res}"))
+            }
+        })
+        .map_err(|e| Error::ShellCommandFailed(what, e))
+}
@@ -0,0 +1,212 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::Deserialize;
+
+use super::run_status;
+use crate::{
+    cache::Cache,
+    config::Package,
+    error::Error,
+    installed_packages::InstalledKind,
+    managed_package::{
+        parse_version_req, LatestVersion, ManagedPackage, PackageDetails, PackageVersion,
+    },
+    npm_registry::NpmPackage,
+    package_provider::{PackageProvider, ProviderContext},
+};
+
+pub struct NpmPackageSpec<'a> {
+    name: &'a str,
+    version_req: Option<semver::VersionReq>,
+}
+
+impl<'a> NpmPackageSpec<'a> {
+    pub fn new(name: &'a str, version_req: Option<&str>) -> Result<Self, Error> {
+        Ok(Self {
+            name,
+            version_req: parse_version_req(name, version_req)?,
+        })
+    }
+
+    fn from_package(package: &'a ManagedPackage) -> Result<Self, Error> {
+        let Package::NpmPackage {
+            package_name,
+            version,
+            ..
+        } = package.package()
+        else {
+            return Err(Error::UnsupportedPackage(package.name().into(), "npm"));
+        };
+        Self::new(
+            package_name.as_deref().unwrap_or(package.name()),
+            version.as_deref(),
+        )
+    }
+
+    /// The name of the package on npm
+    pub fn package_name(&self) -> &str {
+        self.name
+    }
+}
+
+#[derive(Deserialize)]
+struct PackageJson {
+    version: String,
+}
+
+/// The node packages installed globally using `npm install -g --prefix <prefix>`
+pub struct NpmState {
+    prefix: PathBuf,
+}
+
+impl NpmState {
+    pub fn new(prefix: &Path) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+
+    fn package_json_path(&self, name: &str) -> PathBuf {
+        self.prefix
+            .join("lib/node_modules")
+            .join(name)
+            .join("package.json")
+    }
+
+    /// Returns the installed version by reading the `package.json` of the package,
+    /// this avoids running `npm ls` which is slow
+    pub fn package_version(&self, spec: &NpmPackageSpec) -> Result<PackageVersion, Error> {
+        let path = self.package_json_path(spec.name);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(PackageVersion::None),
+            Err(e) => return Err(Error::ReadNpmPackage(path, e.to_string())),
+        };
+        let package: PackageJson = serde_json::from_slice(&data)
+            .map_err(|e| Error::ReadNpmPackage(path.clone(), e.to_string()))?;
+        semver::Version::parse(&package.version)
+            .map(PackageVersion::Semver)
+            .map_err(|e| Error::ReadNpmPackage(path, e.to_string()))
+    }
+
+    pub fn latest_package_version(
+        &self,
+        spec: &NpmPackageSpec,
+        cache: &Cache,
+    ) -> Result<LatestVersion, Error> {
+        let versions = NpmPackage::new(spec.name, cache).get_versions()?;
+        Ok(LatestVersion::select(spec.version_req.as_ref(), versions))
+    }
+
+    /// Install the package, `version` is installed exactly unless it is [PackageVersion::None]
+    pub fn install_package(
+        &self,
+        spec: &NpmPackageSpec,
+        version: &PackageVersion,
+    ) -> Result<(), Error> {
+        let package = match version {
+            PackageVersion::Semver(version) => format!("{}@{version}", spec.name),
+            PackageVersion::None | PackageVersion::Commit { .. } | PackageVersion::Other(_) => {
+                spec.name.into()
+            }
+        };
+        run_status(
+            "npm install -g",
+            Command::new("npm")
+                .args(["install", "--global", "--prefix"])
+                .arg(&self.prefix)
+                .arg(package),
+        )
+    }
+
+    pub fn is_installed(&self, name: &str) -> bool {
+        self.package_json_path(name).exists()
+    }
+
+    pub fn uninstall_package(&self, name: &str) -> Result<(), Error> {
+        run_status(
+            "npm uninstall -g",
+            Command::new("npm")
+                .args(["uninstall", "--global", "--prefix"])
+                .arg(&self.prefix)
+                .arg(name),
+        )
+    }
+}
+
+impl PackageProvider for NpmState {
+    fn handles(&self, package: &Package) -> bool {
+        matches!(package, Package::NpmPackage { .. })
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::Npm { .. })
+    }
+
+    fn installed_version(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        self.package_version(&NpmPackageSpec::from_package(package)?)
+    }
+
+    fn latest_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        self.latest_package_version(&NpmPackageSpec::from_package(package)?, ctx.cache())
+    }
+
+    fn details(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageDetails, Error> {
+        let spec = NpmPackageSpec::from_package(package)?;
+        Ok(PackageDetails {
+            source: Some(format!("npm package {}", spec.name)),
+            location: Some(self.prefix.join("lib/node_modules").join(spec.name)),
+            ..Default::default()
+        })
+    }
+
+    fn install(
+        &mut self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        let spec = NpmPackageSpec::from_package(package)?;
+        self.install_package(&spec, version)?;
+        self.package_version(&spec)
+    }
+
+    fn installed_kind(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        Ok(InstalledKind::Npm {
+            package_name: NpmPackageSpec::from_package(package)?.package_name().into(),
+        })
+    }
+
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        match kind {
+            InstalledKind::Npm { package_name } => self.is_installed(package_name),
+            _ => false,
+        }
+    }
+
+    fn uninstall(&mut self, _ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        match kind {
+            InstalledKind::Npm { package_name } => self.uninstall_package(package_name),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Package,
+    error::Error,
+    installed_packages::InstalledKind,
+    managed_package::{LatestVersion, ManagedPackage, PackageDetails, PackageVersion},
+    package_provider::{PackageProvider, ProviderContext},
+};
+
+/// Bump this when the protocol changes in an incompatible way
+pub const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Operation {
+    /// Returns the `installed` version
+    Status,
+    /// Returns the `latest` version and optionally the `available` version
+    Latest,
+    /// Install the requested `version`, or the latest if it is missing, and
+    /// returns the `installed` version
+    Install,
+    /// Uninstall the package, it is removed from the config so `args` is empty
+    Uninstall,
+}
+
+/// The request written to stdin of the provider, followed by a newline
+#[derive(Serialize)]
+struct Request<'a> {
+    protocol: u32,
+    operation: Operation,
+    package: &'a str,
+    args: BTreeMap<&'a str, &'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<WireVersion>,
+}
+
+/// The response read from stdout of the provider, versions that are `null`
+/// or missing means there is no such version
+#[derive(Deserialize)]
+struct Response {
+    protocol: u32,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    installed: Option<WireVersion>,
+    #[serde(default)]
+    latest: Option<WireVersion>,
+    #[serde(default)]
+    available: Option<WireVersion>,
+}
+
+/// A version as sent over the protocol, such as `{"type": "semver", "version": "1.2.3"}`
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WireVersion {
+    Semver {
+        version: String,
+    },
+    Commit {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        date: Option<jiff::Timestamp>,
+    },
+    Other {
+        version: String,
+    },
+}
+
+impl WireVersion {
+    fn from_version(version: &PackageVersion) -> Option<Self> {
+        match version {
+            PackageVersion::None => None,
+            PackageVersion::Commit { id, date } => Some(Self::Commit {
+                id: id.clone(),
+                date: *date,
+            }),
+            PackageVersion::Semver(version) => Some(Self::Semver {
+                version: version.to_string(),
+            }),
+            PackageVersion::Other(version) => Some(Self::Other {
+                version: version.clone(),
+            }),
+        }
+    }
+
+    fn into_version(version: Option<Self>) -> Result<PackageVersion, String> {
+        match version {
+            None => Ok(PackageVersion::None),
+            Some(Self::Commit { id, date }) => Ok(PackageVersion::Commit { id, date }),
+            Some(Self::Semver { version }) => semver::Version::parse(&version)
+                .map(PackageVersion::Semver)
+                .map_err(|e| format!("invalid version {version:?}: {e}")),
+            Some(Self::Other { version }) => Ok(PackageVersion::Other(version)),
+        }
+    }
+}
+
+/// Describes how to ask the provider executable about a [Package::Plugin]
+struct PluginSpec<'a> {
+    provider: &'a str,
+    package: &'a str,
+    args: BTreeMap<&'a str, &'a str>,
+}
+
+impl<'a> PluginSpec<'a> {
+    fn from_package(package: &'a ManagedPackage) -> Result<Self, Error> {
+        let Package::Plugin { provider, args, .. } = package.package() else {
+            return Err(Error::UnsupportedPackage(package.name().into(), "plugin"));
+        };
+        Ok(Self {
+            provider,
+            package: package.name(),
+            args: args.iter().map(|(k, v)| (&**k, v.as_str())).collect(),
+        })
+    }
+
+    /// The package as recorded when it was installed, without the arguments from the config
+    fn from_installed(kind: &'a InstalledKind) -> Option<Self> {
+        let InstalledKind::Plugin {
+            provider,
+            package_name,
+        } = kind
+        else {
+            return None;
+        };
+        Some(Self {
+            provider,
+            package: package_name,
+            args: BTreeMap::new(),
+        })
+    }
+
+    fn error(&self, msg: impl Into<String>) -> Error {
+        Error::PluginFailed(self.provider.into(), msg.into())
+    }
+
+    /// Run the provider with a single request, its stderr is shown to the user
+    fn call(&self, operation: Operation, version: &PackageVersion) -> Result<Response, Error> {
+        let request = Request {
+            protocol: PROTOCOL_VERSION,
+            operation,
+            package: self.package,
+            args: self.args.clone(),
+            version: WireVersion::from_version(version),
+        };
+        let mut data = serde_json::to_vec(&request).map_err(|e| self.error(e.to_string()))?;
+        data.push(b'\n');
+
+        log::debug!(
+            "Running package provider {:?} for {:?}",
+            self.provider,
+            self.package
+        );
+        let mut child = Command::new(self.provider)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| self.error(e.to_string()))?;
+
+        // NOTE: The request is small, so writing all of it before reading the response can't deadlock
+        let res = child.stdin.take().unwrap().write_all(&data);
+        let out = child
+            .wait_with_output()
+            .map_err(|e| self.error(e.to_string()))?;
+        if !out.status.success() {
+            return Err(self.error(format!("returned status {}", out.status)));
+        }
+        res.map_err(|e| self.error(format!("failed to write request: {e}")))?;
+
+        let response: Response = serde_json::from_slice(&out.stdout)
+            .map_err(|e| self.error(format!("invalid response: {e}")))?;
+        if response.protocol != PROTOCOL_VERSION {
+            return Err(self.error(format!(
+                "unsupported protocol version {} (want {PROTOCOL_VERSION})",
+                response.protocol
+            )));
+        }
+        if let Some(e) = response.error {
+            return Err(self.error(e));
+        }
+        Ok(response)
+    }
+
+    fn parse_version(&self, version: Option<WireVersion>) -> Result<PackageVersion, Error> {
+        WireVersion::into_version(version).map_err(|e| self.error(e))
+    }
+}
+
+/// The packages installed by external provider executables
+pub struct PluginPackages;
+
+impl PackageProvider for PluginPackages {
+    fn handles(&self, package: &Package) -> bool {
+        matches!(package, Package::Plugin { .. })
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::Plugin { .. })
+    }
+
+    fn installed_version(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        let spec = PluginSpec::from_package(package)?;
+        let response = spec.call(Operation::Status, &PackageVersion::None)?;
+        spec.parse_version(response.installed)
+    }
+
+    fn latest_version(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        let spec = PluginSpec::from_package(package)?;
+        let response = spec.call(Operation::Latest, &PackageVersion::None)?;
+        let allowed = spec.parse_version(response.latest)?;
+        Ok(match response.available {
+            Some(available) => LatestVersion {
+                allowed,
+                available: spec.parse_version(Some(available))?,
+            },
+            None => LatestVersion::unconstrained(allowed),
+        })
+    }
+
+    fn details(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageDetails, Error> {
+        let spec = PluginSpec::from_package(package)?;
+        Ok(PackageDetails {
+            source: Some(format!("provider {}", spec.provider)),
+            ..Default::default()
+        })
+    }
+
+    fn install(
+        &mut self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        let spec = PluginSpec::from_package(package)?;
+        let response = spec.call(Operation::Install, version)?;
+        spec.parse_version(response.installed)
+    }
+
+    fn installed_kind(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        let spec = PluginSpec::from_package(package)?;
+        Ok(InstalledKind::Plugin {
+            provider: spec.provider.into(),
+            package_name: spec.package.into(),
+        })
+    }
+
+    /// The provider needs the arguments from the config to check the package,
+    /// so it is assumed to still be installed
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::Plugin { .. })
+    }
+
+    fn uninstall(&mut self, _ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        match PluginSpec::from_installed(kind) {
+            Some(spec) => spec
+                .call(Operation::Uninstall, &PackageVersion::None)
+                .map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use std::{collections::HashMap, process::Command, sync::OnceLock};
+
+use serde::Deserialize;
+
+use super::{run_command, run_status};
+use crate::{
+    cache::Cache,
+    config::{Package, PackageDefaults, PythonInstaller},
+    error::Error,
+    installed_packages::InstalledKind,
+    managed_package::{
+        parse_loose_version, parse_version_req, LatestVersion, ManagedPackage, PackageDetails,
+        PackageVersion,
+    },
+    package_provider::{PackageProvider, ProviderContext},
+    pypi::PyPiPackage,
+};
+
+/// Normalize the name of a python package as described in PEP 503
+fn normalize_name(name: &str) -> String {
+    let mut ret = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !ret.ends_with('-') {
+                ret.push('-');
+            }
+        } else {
+            ret.push(c.to_ascii_lowercase());
+        }
+    }
+    ret
+}
+
+pub struct PythonToolSpec {
+    name: String,
+    installer: PythonInstaller,
+    version_req: Option<semver::VersionReq>,
+}
+
+impl PythonToolSpec {
+    pub fn new(
+        name: &str,
+        installer: Option<PythonInstaller>,
+        version_req: Option<&str>,
+        defaults: &PackageDefaults,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            name: normalize_name(name),
+            installer: installer
+                .or(defaults.python_installer)
+                .unwrap_or(PythonInstaller::Pipx),
+            version_req: parse_version_req(name, version_req)?,
+        })
+    }
+
+    fn from_package(package: &ManagedPackage) -> Result<Self, Error> {
+        let Package::PythonTool {
+            package_name,
+            installer,
+            version,
+            ..
+        } = package.package()
+        else {
+            return Err(Error::UnsupportedPackage(package.name().into(), "python"));
+        };
+        Self::new(
+            package_name.as_deref().unwrap_or(package.name()),
+            *installer,
+            version.as_deref(),
+            package.defaults(),
+        )
+    }
+
+    /// The normalized name of the package on PyPI
+    pub fn package_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn installer(&self) -> PythonInstaller {
+        self.installer
+    }
+}
+
+#[derive(Deserialize)]
+struct PipxList {
+    venvs: HashMap<String, PipxVenv>,
+}
+
+#[derive(Deserialize)]
+struct PipxVenv {
+    metadata: PipxMetadata,
+}
+
+#[derive(Deserialize)]
+struct PipxMetadata {
+    main_package: PipxPackage,
+}
+
+#[derive(Deserialize)]
+struct PipxPackage {
+    package: String,
+    package_version: String,
+}
+
+type InstalledTools = HashMap<String, semver::Version>;
+
+/// The python tools installed using pipx and uv, only listed once needed as
+/// the installers may not be available
+#[derive(Default)]
+pub struct PythonState {
+    pipx: OnceLock<Result<InstalledTools, String>>,
+    uv: OnceLock<Result<InstalledTools, String>>,
+}
+
+impl PythonState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn list_pipx() -> Result<InstalledTools, Error> {
+        let out = run_command(
+            "pipx list --json",
+            Command::new("pipx").args(["list", "--json"]),
+        )?;
+        Self::parse_pipx_list(&out)
+    }
+
+    fn list_uv() -> Result<InstalledTools, Error> {
+        let out = run_command("uv tool list", Command::new("uv").args(["tool", "list"]))?;
+        Ok(Self::parse_uv_list(&out))
+    }
+
+    /// Parse the output of `pipx list --json`, returns the normalized names of
+    /// the installed packages and their versions
+    pub fn parse_pipx_list(out: &str) -> Result<InstalledTools, Error> {
+        let list: PipxList = serde_json::from_str(out)
+            .map_err(|e| Error::ShellCommandFailed("pipx list --json", e.to_string()))?;
+
+        let mut ret = InstalledTools::new();
+        for (name, venv) in list.venvs {
+            let PipxPackage {
+                package,
+                package_version,
+            } = venv.metadata.main_package;
+            if let Some(version) = parse_loose_version(&package_version) {
+                ret.insert(normalize_name(&package), version);
+            } else {
+                log::warn!("Ignoring pipx venv {name:?} with unknown version {package_version:?}");
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Parse the output of `uv tool list`, such as `ruff v0.6.9` followed by the
+    /// binaries, returns the normalized names of the installed packages and their versions
+    pub fn parse_uv_list(out: &str) -> InstalledTools {
+        let mut ret = InstalledTools::new();
+        for line in out.lines().filter(|v| !v.starts_with('-')) {
+            let mut parts = line.split_whitespace();
+            let (Some(name), Some(version)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            if let Some(version) = parse_loose_version(version) {
+                ret.insert(normalize_name(name), version);
+            } else {
+                log::warn!("Ignoring unexpected line from `uv tool list`: {line:?}");
+            }
+        }
+        ret
+    }
+
+    fn installed(&self, installer: PythonInstaller) -> Result<&InstalledTools, Error> {
+        let (what, res) = match installer {
+            PythonInstaller::Pipx => (
+                "pipx list --json",
+                self.pipx
+                    .get_or_init(|| Self::list_pipx().map_err(|e| e.to_string())),
+            ),
+            PythonInstaller::Uv => (
+                "uv tool list",
+                self.uv
+                    .get_or_init(|| Self::list_uv().map_err(|e| e.to_string())),
+            ),
+        };
+        res.as_ref()
+            .map_err(|e| Error::ShellCommandFailed(what, e.clone()))
+    }
+
+    pub fn package_version(&self, spec: &PythonToolSpec) -> Result<PackageVersion, Error> {
+        Ok(self
+            .installed(spec.installer)?
+            .get(&spec.name)
+            .map_or(PackageVersion::None, |v| PackageVersion::Semver(v.clone())))
+    }
+
+    pub fn latest_package_version(
+        &self,
+        spec: &PythonToolSpec,
+        cache: &Cache,
+    ) -> Result<LatestVersion, Error> {
+        let versions = PyPiPackage::new(&spec.name, cache).get_versions()?;
+        Ok(LatestVersion::select(spec.version_req.as_ref(), versions))
+    }
+
+    /// Install the package, `version` is installed exactly unless it is [PackageVersion::None]
+    pub fn install_package(
+        &mut self,
+        spec: &PythonToolSpec,
+        version: &PackageVersion,
+    ) -> Result<(), Error> {
+        let requirement = match version {
+            PackageVersion::Semver(version) => format!("{}=={version}", spec.name),
+            PackageVersion::None | PackageVersion::Commit { .. } | PackageVersion::Other(_) => {
+                spec.name.clone()
+            }
+        };
+
+        match spec.installer {
+            PythonInstaller::Pipx => {
+                run_status(
+                    "pipx install",
+                    Command::new("pipx").args(["install", "--force", &requirement]),
+                )?;
+                self.pipx = OnceLock::new();
+            }
+            PythonInstaller::Uv => {
+                run_status(
+                    "uv tool install",
+                    Command::new("uv").args(["tool", "install", "--force", &requirement]),
+                )?;
+                self.uv = OnceLock::new();
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if the package is installed, failing to list the installed packages counts as not installed
+    pub fn is_installed(&self, installer: PythonInstaller, name: &str) -> bool {
+        self.installed(installer)
+            .is_ok_and(|v| v.contains_key(&normalize_name(name)))
+    }
+
+    pub fn uninstall_package(
+        &mut self,
+        installer: PythonInstaller,
+        name: &str,
+    ) -> Result<(), Error> {
+        match installer {
+            PythonInstaller::Pipx => {
+                run_status(
+                    "pipx uninstall",
+                    Command::new("pipx").args(["uninstall", name]),
+                )?;
+                self.pipx = OnceLock::new();
+            }
+            PythonInstaller::Uv => {
+                run_status(
+                    "uv tool uninstall",
+                    Command::new("uv").args(["tool", "uninstall", name]),
+                )?;
+                self.uv = OnceLock::new();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PackageProvider for PythonState {
+    fn handles(&self, package: &Package) -> bool {
+        matches!(package, Package::PythonTool { .. })
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(
+            kind,
+            InstalledKind::Pipx { .. } | InstalledKind::UvTool { .. }
+        )
+    }
+
+    fn installed_version(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        self.package_version(&PythonToolSpec::from_package(package)?)
+    }
+
+    fn latest_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        self.latest_package_version(&PythonToolSpec::from_package(package)?, ctx.cache())
+    }
+
+    fn details(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageDetails, Error> {
+        let spec = PythonToolSpec::from_package(package)?;
+        let installer = match spec.installer() {
+            PythonInstaller::Pipx => "pipx",
+            PythonInstaller::Uv => "uv",
+        };
+        Ok(PackageDetails {
+            source: Some(format!(
+                "PyPI package {} using {installer}",
+                spec.package_name()
+            )),
+            ..Default::default()
+        })
+    }
+
+    fn install(
+        &mut self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        let spec = PythonToolSpec::from_package(package)?;
+        self.install_package(&spec, version)?;
+        self.package_version(&spec)
+    }
+
+    /// Uses the name from the config rather than the normalized name, to match earlier records
+    fn installed_kind(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        let Package::PythonTool {
+            package_name,
+            installer,
+            ..
+        } = package.package()
+        else {
+            return Err(Error::UnsupportedPackage(package.name().into(), "python"));
+        };
+        let package_name = package_name.as_deref().unwrap_or(package.name()).into();
+        Ok(
+            match installer
+                .or(package.defaults().python_installer)
+                .unwrap_or(PythonInstaller::Pipx)
+            {
+                PythonInstaller::Pipx => InstalledKind::Pipx { package_name },
+                PythonInstaller::Uv => InstalledKind::UvTool { package_name },
+            },
+        )
+    }
+
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        match kind {
+            InstalledKind::Pipx { package_name } => {
+                self.is_installed(PythonInstaller::Pipx, package_name)
+            }
+            InstalledKind::UvTool { package_name } => {
+                self.is_installed(PythonInstaller::Uv, package_name)
+            }
+            _ => false,
+        }
+    }
+
+    fn uninstall(&mut self, _ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        match kind {
+            InstalledKind::Pipx { package_name } => {
+                self.uninstall_package(PythonInstaller::Pipx, package_name)
+            }
+            InstalledKind::UvTool { package_name } => {
+                self.uninstall_package(PythonInstaller::Uv, package_name)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn refresh(&mut self) -> Result<(), Error> {
+        *self = Self::new();
+        Ok(())
+    }
+}
@@ -0,0 +1,339 @@
+use std::{fmt, path::Path, process::Command};
+
+use logix_type::types::ShortStr;
+
+use super::run_status;
+use crate::{
+    config::{Package, SystemPackageMode},
+    error::Error,
+    installed_packages::InstalledKind,
+    managed_package::{LatestVersion, ManagedPackage, PackageDetails, PackageVersion},
+    package_provider::{PackageProvider, ProviderContext},
+};
+
+/// The package managers of the supported distributions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemPackageManager {
+    Apt,
+    Dnf,
+    Pacman,
+}
+
+impl SystemPackageManager {
+    fn from_distro_id(id: &str) -> Option<Self> {
+        match id {
+            "debian" | "ubuntu" => Some(Self::Apt),
+            "fedora" | "rhel" | "centos" => Some(Self::Dnf),
+            "arch" => Some(Self::Pacman),
+            _ => None,
+        }
+    }
+
+    /// Detect the package manager using the `ID` and `ID_LIKE` fields of `/etc/os-release`
+    fn detect(os_release: &str) -> Option<Self> {
+        let mut id = None;
+        let mut id_like = None;
+        for line in os_release.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+                match key.trim() {
+                    "ID" => id = Some(value),
+                    "ID_LIKE" => id_like = Some(value),
+                    _ => {}
+                }
+            }
+        }
+
+        id.into_iter()
+            .chain(id_like.into_iter().flat_map(|v| v.split_whitespace()))
+            .find_map(Self::from_distro_id)
+    }
+
+    /// Parse the output of the query for the installed version of a package,
+    /// `dpkg-query`, `rpm -q` or `pacman -Q`
+    pub fn parse_installed_version(self, out: &str) -> PackageVersion {
+        let version = match self {
+            Self::Apt => out.split_once('\t').and_then(|(status, version)| {
+                // NOTE: Removed packages are still known by dpkg until they are purged
+                status.ends_with(" installed").then(|| version.trim())
+            }),
+            // NOTE: Packages such as the kernel may be installed in several versions
+            Self::Dnf => out.lines().next().map(str::trim),
+            Self::Pacman => out.split_whitespace().nth(1),
+        };
+        other_version(version)
+    }
+
+    /// Parse the output of the query for the candidate version of a package,
+    /// `apt-cache policy`, `dnf repoquery` or `pacman -Si`
+    pub fn parse_candidate_version(self, out: &str) -> PackageVersion {
+        let version = match self {
+            Self::Apt => out
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("Candidate:"))
+                .map(str::trim)
+                .filter(|v| *v != "(none)"),
+            Self::Dnf => out.lines().next().map(str::trim),
+            Self::Pacman => out.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key.trim() == "Version").then(|| value.trim())
+            }),
+        };
+        other_version(version)
+    }
+}
+
+/// The version as reported by the package manager, [PackageVersion::None] if it is missing
+fn other_version(version: Option<&str>) -> PackageVersion {
+    version
+        .filter(|v| !v.is_empty())
+        .map_or(PackageVersion::None, |v| PackageVersion::Other(v.into()))
+}
+
+impl fmt::Display for SystemPackageManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Apt => write!(f, "apt"),
+            Self::Dnf => write!(f, "dnf"),
+            Self::Pacman => write!(f, "pacman"),
+        }
+    }
+}
+
+/// Run a query that fails if the package is unknown, returns `None` in that case
+fn query(what: &'static str, cmd: &mut Command) -> Result<Option<String>, Error> {
+    log::debug!("Running {cmd:?}");
+    let res = cmd
+        .output()
+        .map_err(|e| Error::ShellCommandFailed(what, e.to_string()))?;
+    Ok(res
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&res.stdout).into_owned()))
+}
+
+/// The packages of the distribution, queried using its package manager
+pub struct SystemPackages {
+    manager: Option<SystemPackageManager>,
+}
+
+impl SystemPackages {
+    /// Detect the package manager, a missing `os_release` means no package manager is available
+    pub fn init(os_release: &Path) -> Self {
+        let manager = match std::fs::read_to_string(os_release) {
+            Ok(data) => SystemPackageManager::detect(&data),
+            Err(e) => {
+                log::debug!("Failed to read {os_release:?}: {e}");
+                None
+            }
+        };
+        Self { manager }
+    }
+
+    /// The package manager of the distribution, if it is supported
+    pub fn manager(&self) -> Option<SystemPackageManager> {
+        self.manager
+    }
+
+    /// The name of a [Package::System] for the detected package manager
+    fn package_name<'a>(&self, package: &ManagedPackage<'a>) -> Result<Option<&'a str>, Error> {
+        let Package::System {
+            apt, dnf, pacman, ..
+        } = package.package()
+        else {
+            return Err(Error::UnsupportedPackage(package.name().into(), "system"));
+        };
+        let name: &Option<ShortStr> = match self.manager {
+            Some(SystemPackageManager::Apt) => apt,
+            Some(SystemPackageManager::Dnf) => dnf,
+            Some(SystemPackageManager::Pacman) => pacman,
+            None => return Ok(None),
+        };
+        Ok(name.as_deref())
+    }
+
+    /// Returns the installed version of the package, [PackageVersion::None] if it is missing
+    pub fn package_version(&self, name: &str) -> Result<PackageVersion, Error> {
+        let Some(manager) = self.manager else {
+            return Ok(PackageVersion::None);
+        };
+
+        let out = match manager {
+            SystemPackageManager::Apt => query(
+                "dpkg-query",
+                Command::new("dpkg-query").args(["-W", "-f=${Status}\t${Version}", name]),
+            )?,
+            SystemPackageManager::Dnf => query(
+                "rpm -q",
+                Command::new("rpm").args(["-q", "--qf", "%{VERSION}-%{RELEASE}\n", name]),
+            )?,
+            SystemPackageManager::Pacman => {
+                query("pacman -Q", Command::new("pacman").args(["-Q", name]))?
+            }
+        };
+
+        Ok(out.map_or(PackageVersion::None, |out| {
+            manager.parse_installed_version(&out)
+        }))
+    }
+
+    /// Returns the version the package manager would install, only using the
+    /// package lists that are already downloaded
+    pub fn candidate_version(&self, name: &str) -> Result<PackageVersion, Error> {
+        let Some(manager) = self.manager else {
+            return Ok(PackageVersion::None);
+        };
+
+        let out = match manager {
+            SystemPackageManager::Apt => query(
+                "apt-cache policy",
+                Command::new("apt-cache").args(["policy", name]),
+            )?,
+            SystemPackageManager::Dnf => query(
+                "dnf repoquery",
+                Command::new("dnf").args([
+                    "--cacheonly",
+                    "--quiet",
+                    "repoquery",
+                    "--latest-limit=1",
+                    "--queryformat",
+                    "%{version}-%{release}\n",
+                    name,
+                ]),
+            )?,
+            SystemPackageManager::Pacman => {
+                query("pacman -Si", Command::new("pacman").args(["-Si", name]))?
+            }
+        };
+
+        Ok(out.map_or(PackageVersion::None, |out| {
+            manager.parse_candidate_version(&out)
+        }))
+    }
+
+    /// Install or upgrade the package using `sudo`
+    pub fn install_package(&self, name: &str) -> Result<(), Error> {
+        let Some(manager) = self.manager else {
+            return Err(Error::NoSystemPackageManager);
+        };
+
+        match manager {
+            SystemPackageManager::Apt => run_status(
+                "sudo apt-get install",
+                Command::new("sudo").args(["apt-get", "install", "--yes", name]),
+            ),
+            SystemPackageManager::Dnf => run_status(
+                "sudo dnf install",
+                Command::new("sudo").args(["dnf", "install", "--assumeyes", name]),
+            ),
+            SystemPackageManager::Pacman => run_status(
+                "sudo pacman -S",
+                Command::new("sudo").args(["pacman", "-S", "--needed", "--noconfirm", name]),
+            ),
+        }
+    }
+}
+
+impl PackageProvider for SystemPackages {
+    fn handles(&self, package: &Package) -> bool {
+        matches!(package, Package::System { .. })
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::System { .. })
+    }
+
+    fn installed_version(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        match self.package_name(package)? {
+            Some(name) => self.package_version(name),
+            None => Ok(PackageVersion::None),
+        }
+    }
+
+    fn latest_version(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        Ok(LatestVersion::unconstrained(
+            match self.package_name(package)? {
+                Some(name) => self.candidate_version(name)?,
+                None => PackageVersion::None,
+            },
+        ))
+    }
+
+    fn details(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageDetails, Error> {
+        Ok(PackageDetails {
+            source: self
+                .manager
+                .zip(self.package_name(package)?)
+                .map(|(manager, name)| format!("{manager} package {name}")),
+            ..Default::default()
+        })
+    }
+
+    fn install(
+        &mut self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+        _version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        let name = self
+            .package_name(package)?
+            .ok_or(Error::NoSystemPackageManager)?;
+        if self.is_check_only(package) {
+            return Err(Error::SystemPackageCheckOnly(name.into()));
+        }
+        // NOTE: The package manager always installs the candidate version
+        self.install_package(name)?;
+        self.package_version(name)
+    }
+
+    fn installed_kind(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        Ok(InstalledKind::System {
+            package_name: self.package_name(package)?.unwrap_or(package.name()).into(),
+        })
+    }
+
+    fn is_check_only(&self, package: &ManagedPackage) -> bool {
+        !matches!(
+            package.package(),
+            Package::System {
+                mode: Some(SystemPackageMode::Install),
+                ..
+            }
+        )
+    }
+
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        match kind {
+            InstalledKind::System { package_name } => self
+                .package_version(package_name)
+                .is_ok_and(|v| v != PackageVersion::None),
+            _ => false,
+        }
+    }
+
+    fn uninstall(&mut self, _ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        if let InstalledKind::System { package_name } = kind {
+            // NOTE: Other packages may depend on it, so leave it to the package manager
+            log::warn!(
+                "System packages are not uninstalled automatically, remove {package_name:?} using the package manager if it is no longer needed"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+use std::path::{Path, PathBuf};
+
+use logix_type::types::ShortStr;
+
+use crate::{
+    config::Package,
+    error::Error,
+    file_index::ContentHash,
+    helpers::archive,
+    installed_packages::InstalledKind,
+    managed_package::{LatestVersion, ManagedPackage, PackageDetails, PackageVersion},
+    package_provider::{PackageProvider, ProviderContext},
+    url_fetch::UrlFetch,
+};
+
+/// The name of the symlink pointing to the directory of the installed version
+const CURRENT_LINK: &str = "current";
+
+/// Describes a versioned archive that is downloaded and extracted
+pub struct ArchiveSpec<'a> {
+    package: &'a str,
+    url: String,
+    version: semver::Version,
+    sha256: &'a str,
+    strip_components: usize,
+    bins: &'a [ShortStr],
+}
+
+impl<'a> ArchiveSpec<'a> {
+    pub fn new(
+        package: &'a str,
+        url_template: &str,
+        version: &str,
+        sha256: &'a str,
+        strip_components: Option<u32>,
+        bins: Option<&'a [ShortStr]>,
+    ) -> Result<Self, Error> {
+        let parsed = semver::Version::parse(version.strip_prefix('v').unwrap_or(version))
+            .map_err(|e| Error::InvalidVersion(package.into(), version.into(), e.to_string()))?;
+
+        Ok(Self {
+            package,
+            url: url_template.replace("{version}", version),
+            version: parsed,
+            sha256,
+            strip_components: strip_components.unwrap_or(0) as usize,
+            bins: bins.unwrap_or_default(),
+        })
+    }
+
+    fn from_package(package: &'a ManagedPackage) -> Result<Self, Error> {
+        let Package::Archive {
+            url_template,
+            version,
+            sha256,
+            strip_components,
+            bins,
+            ..
+        } = package.package()
+        else {
+            return Err(Error::UnsupportedPackage(package.name().into(), "archive"));
+        };
+        Self::new(
+            package.name(),
+            url_template,
+            version,
+            sha256,
+            *strip_components,
+            bins.as_deref(),
+        )
+    }
+
+    /// The version declared in the config
+    pub fn version(&self) -> PackageVersion {
+        PackageVersion::Semver(self.version.clone())
+    }
+
+    /// The name of the archive, used to detect the format
+    fn archive_name(&self) -> &str {
+        let path = self.url.split(['?', '#']).next().unwrap_or_default();
+        path.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// Returns the version the `current` symlink in `dir` points to
+    pub fn installed_version(dir: &Path) -> PackageVersion {
+        std::fs::read_link(dir.join(CURRENT_LINK))
+            .ok()
+            .and_then(|target| semver::Version::parse(target.file_name()?.to_str()?).ok())
+            .map_or(PackageVersion::None, PackageVersion::Semver)
+    }
+
+    /// Download and extract the archive into a directory for the version in
+    /// `dir`, then switch the `current` symlink to it and link the binaries
+    /// into `bin_dir`
+    pub fn install(&self, dir: &Path, bin_dir: &Path) -> Result<(), Error> {
+        log::info!("Downloading {:?} for package {:?}", self.url, self.package);
+
+        let data = UrlFetch::new(&self.url)?
+            .follow_location()?
+            .get()?
+            .into_bytes();
+
+        let actual = ContentHash::of_bytes(&data);
+        if !self.sha256.eq_ignore_ascii_case(actual.as_hex()) {
+            return Err(Error::ChecksumMismatch(
+                self.url.clone(),
+                self.sha256.into(),
+                actual.as_hex().into(),
+            ));
+        }
+
+        let version = self.version.to_string();
+        let version_dir = dir.join(&version);
+        let tmp_dir = dir.join(format!("{version}.tmp"));
+        let install_error = |path: &Path, e: std::io::Error| {
+            Error::InstallArchive(path.to_path_buf(), e.to_string())
+        };
+
+        if tmp_dir.exists() {
+            std::fs::remove_dir_all(&tmp_dir).map_err(|e| install_error(&tmp_dir, e))?;
+        }
+        archive::extract_to_dir(self.archive_name(), &data, &tmp_dir, self.strip_components)?;
+        if version_dir.exists() {
+            std::fs::remove_dir_all(&version_dir).map_err(|e| install_error(&version_dir, e))?;
+        }
+        std::fs::rename(&tmp_dir, &version_dir).map_err(|e| install_error(&version_dir, e))?;
+
+        let current = dir.join(CURRENT_LINK);
+        replace_symlink(&current, Path::new(&version)).map_err(|e| install_error(&current, e))?;
+
+        for bin in self.bins {
+            let target = current.join(&**bin);
+            if !target.exists() {
+                return Err(Error::FileNotInArchive(
+                    self.archive_name().into(),
+                    bin.to_string(),
+                ));
+            }
+            let link = bin_dir.join(Path::new(&**bin).file_name().unwrap_or_default());
+            std::fs::create_dir_all(bin_dir).map_err(|e| install_error(bin_dir, e))?;
+            replace_symlink(&link, &target).map_err(|e| install_error(&link, e))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Remove the extracted archives in `dir` and the links to them in `bin_dir`
+fn uninstall(dir: &Path, bin_dir: &Path) -> Result<(), Error> {
+    let install_error =
+        |path: &Path, e: std::io::Error| Error::InstallArchive(path.to_path_buf(), e.to_string());
+
+    if let Ok(entries) = std::fs::read_dir(bin_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if std::fs::read_link(&path).is_ok_and(|target| target.starts_with(dir)) {
+                std::fs::remove_file(&path).map_err(|e| install_error(&path, e))?;
+            }
+        }
+    }
+
+    std::fs::remove_dir_all(dir).map_err(|e| install_error(dir, e))
+}
+
+/// The archives downloaded and extracted into versioned directories
+pub struct ArchivePackages;
+
+impl PackageProvider for ArchivePackages {
+    fn handles(&self, package: &Package) -> bool {
+        matches!(package, Package::Archive { .. })
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::Archive { .. })
+    }
+
+    fn installed_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        Ok(ArchiveSpec::installed_version(
+            &ctx.archive_package_dir(package.name()),
+        ))
+    }
+
+    /// The declared version, as the checksum is only known for it
+    fn latest_version(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        Ok(LatestVersion::unconstrained(
+            ArchiveSpec::from_package(package)?.version(),
+        ))
+    }
+
+    fn details(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageDetails, Error> {
+        let spec = ArchiveSpec::from_package(package)?;
+        Ok(PackageDetails {
+            bins: spec
+                .bins
+                .iter()
+                .map(|bin| {
+                    // NOTE: The binaries are linked by their file name, see `install`
+                    ctx.user_bin()
+                        .as_path()
+                        .join(Path::new(&**bin).file_name().unwrap_or_default())
+                })
+                .collect(),
+            source: Some(spec.url),
+            location: Some(ctx.archive_package_dir(package.name())),
+            environment: Vec::new(),
+        })
+    }
+
+    fn install(
+        &mut self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        let spec = ArchiveSpec::from_package(package)?;
+        // NOTE: The checksum is only known for the declared version, so that is always installed
+        if *version != spec.version() {
+            log::warn!(
+                "Installing the declared version {} of package {:?} instead of {version}",
+                spec.version(),
+                package.name()
+            );
+        }
+        spec.install(&ctx.archive_package_dir(package.name()), ctx.user_bin())?;
+        Ok(spec.version())
+    }
+
+    fn installed_kind(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        Ok(InstalledKind::Archive {
+            dir: ctx.archive_package_dir(package.name()),
+        })
+    }
+
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        match kind {
+            InstalledKind::Archive { dir } => {
+                ArchiveSpec::installed_version(dir) != PackageVersion::None
+            }
+            _ => false,
+        }
+    }
+
+    fn uninstall(&mut self, ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        match kind {
+            InstalledKind::Archive { dir } => uninstall(dir, ctx.user_bin()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Atomically point the symlink at `link` to `target`
+#[cfg(unix)]
+fn replace_symlink(link: &Path, target: &Path) -> std::io::Result<()> {
+    let mut tmp_name = link.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".logix-tmp");
+    let tmp: PathBuf = link.with_file_name(tmp_name);
+
+    if tmp.symlink_metadata().is_ok() {
+        std::fs::remove_file(&tmp)?;
+    }
+
+    std::os::unix::fs::symlink(target, &tmp)?;
+    std::fs::rename(&tmp, link)
+}
+
+#[cfg(not(unix))]
+fn replace_symlink(_link: &Path, target: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("symlinks to {target:?} are only supported on unix"),
+    ))
+}
@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{based_path::BasedPath, error::Error, managed_package::PackageVersion};
+
+/// Bump this when the format of the state file changes in an incompatible way
+const STATE_VERSION: u32 = 1;
+
+/// How a package was installed, this is what is needed to uninstall it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InstalledKind {
+    /// Installed using `cargo install`
+    RustCrate { crate_name: String },
+    /// Built from the sources in `local_dir` using the commands from the config
+    Custom { local_dir: PathBuf },
+    /// The binary `bin` was downloaded from a GitHub release
+    GitHubRelease { bin: PathBuf },
+    /// Extracted into versioned directories in `dir`
+    Archive { dir: PathBuf },
+    /// Installed using `pipx install`
+    Pipx { package_name: String },
+    /// Installed using `uv tool install`
+    UvTool { package_name: String },
+    /// Installed using `npm install -g`
+    Npm { package_name: String },
+    /// The binary `bin` was installed using `go install`
+    Go { bin: PathBuf },
+    /// Installed using the package manager of the distribution
+    System { package_name: String },
+    /// Installed by the external provider executable `provider`
+    Plugin {
+        provider: String,
+        package_name: String,
+    },
+    /// Installed by a provider registered by a library user, see [crate::config::Package::Extension]
+    Extension {
+        provider: String,
+        package_name: String,
+    },
+}
+
+/// A package that was installed by logix
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledPackage {
+    /// The name of the package in the config at the time it was installed
+    pub name: String,
+    pub kind: InstalledKind,
+    pub version: PackageVersion,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StateData {
+    version: u32,
+    packages: Vec<InstalledPackage>,
+}
+
+/// Keeps track of the packages installed by logix, so that packages removed
+/// from the config can be uninstalled
+pub struct InstalledPackages {
+    path: BasedPath,
+    packages: Vec<InstalledPackage>,
+}
+
+impl InstalledPackages {
+    /// Load the state stored at `path`, a missing file means nothing has been installed yet
+    pub fn load(path: BasedPath) -> Result<Self, Error> {
+        let packages = match std::fs::read(&path) {
+            Ok(data) => {
+                let data: StateData = serde_json::from_slice(&data)
+                    .map_err(|e| Error::ReadInstalledState(path.clone(), e.to_string()))?;
+                if data.version != STATE_VERSION {
+                    return Err(Error::ReadInstalledState(
+                        path,
+                        format!(
+                            "unsupported version {} (want {STATE_VERSION})",
+                            data.version
+                        ),
+                    ));
+                }
+                data.packages
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(Error::ReadInstalledState(path, e.to_string())),
+        };
+
+        Ok(Self { path, packages })
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        let data = StateData {
+            version: STATE_VERSION,
+            packages: self.packages.clone(),
+        };
+
+        let tmp_path = self.path.with_extension("json.tmp");
+
+        std::fs::create_dir_all(self.path.parent().unwrap())
+            .and_then(|()| {
+                std::fs::write(
+                    &tmp_path,
+                    serde_json::to_vec_pretty(&data).map_err(std::io::Error::other)?,
+                )
+            })
+            .and_then(|()| std::fs::rename(&tmp_path, &self.path))
+            .map_err(|e| Error::WriteInstalledState(self.path.clone(), e.to_string()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &InstalledPackage> {
+        self.packages.iter()
+    }
+
+    /// Record that the package was installed, replacing any previous record of the same kind
+    pub fn insert(&mut self, package: InstalledPackage) -> Result<(), Error> {
+        if self.packages.contains(&package) {
+            return Ok(());
+        }
+
+        self.packages.retain(|v| v.kind != package.kind);
+        self.packages.push(package);
+        self.save()
+    }
+
+    /// Forget the package after it has been uninstalled
+    pub fn remove(&mut self, kind: &InstalledKind) -> Result<(), Error> {
+        let len = self.packages.len();
+        self.packages.retain(|v| v.kind != *kind);
+        if self.packages.len() != len {
+            self.save()
+        } else {
+            Ok(())
+        }
+    }
+}
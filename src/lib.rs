@@ -1,22 +1,39 @@
-use crate::{env::Env, error::Error, managed_file::Owner};
+use crate::{
+    based_path::BasedPath, env::Env, error::Error, file_index::FileIndex,
+    installed_packages::InstalledPackage, lockfile::Lockfile, managed_file::Owner,
+    system_state::SystemState,
+};
 use config::{ConfigDir, Filter, Package};
 use logix_type::LogixLoader;
 use logix_vfs::{MemFs, RelFs};
 use managed_file::{FileStatus, ManagedFile};
-use managed_files::ManagedFiles;
+use managed_files::{ManagedFiles, DEFAULT_PRIORITY};
 use managed_package::ManagedPackage;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use std::fmt::Write as _;
 
 pub mod based_path;
 mod cache;
 pub mod config;
+mod crates_io;
 pub mod env;
 pub mod error;
+pub mod file_index;
+mod git_host;
+mod git_remote;
+mod gitea;
 mod github;
+mod gitlab;
+mod go_proxy;
 mod helpers;
+pub mod installed_packages;
+pub mod lockfile;
 pub mod managed_file;
 pub mod managed_files;
 pub mod managed_package;
+mod npm_registry;
+pub mod package_provider;
+mod pypi;
 pub mod system_state;
 mod url_fetch;
 mod walk_dir;
@@ -26,18 +43,36 @@ mod walk_dir;
 pub struct Logix {
     env: Env,
     config: config::Logix,
+    lockfile: Lockfile,
 }
 
 impl Logix {
     /// Load the logix instance from the specified environment. This includes loading the config files.
     pub fn load(env: Env) -> Result<Self, Error> {
         let mut loader = LogixLoader::new(RelFs::new(env.logix_root()));
+        let config = loader.load_file("root.logix")?;
+        let lockfile = Lockfile::load(&Self::lockfile_path_in(&env)?)?;
         Ok(Self {
             env,
-            config: loader.load_file("root.logix")?,
+            config,
+            lockfile,
         })
     }
 
+    fn lockfile_path_in(env: &Env) -> Result<BasedPath, Error> {
+        env.logix_root().join(lockfile::LOCKFILE_NAME)
+    }
+
+    /// Retrieve the versions locked by `logix.lock`
+    pub fn lockfile(&self) -> &Lockfile {
+        &self.lockfile
+    }
+
+    /// The path of `logix.lock`, it is stored next to `root.logix`
+    pub fn lockfile_path(&self) -> Result<BasedPath, Error> {
+        Self::lockfile_path_in(&self.env)
+    }
+
     /// Retrieve the raw config
     pub fn config(&self) -> &config::Logix {
         &self.config
@@ -48,7 +83,8 @@ impl Logix {
         &self.env
     }
 
-    /// Returns a list of files managed by logix
+    /// Returns a list of files managed by logix, fails if several owners claim
+    /// the same file with the same priority
     pub fn calculate_managed_files(&self) -> Result<Vec<ManagedFile>, Error> {
         let config::Logix { home } = &self.config;
         let mut ret = ManagedFiles::new(&self.env);
@@ -61,6 +97,7 @@ impl Logix {
                 editor: _,
                 ssh,
                 packages,
+                package_defaults: _,
             } = home;
             match shell {
                 Some(config::Shell::Bash) => {}
@@ -84,15 +121,71 @@ impl Logix {
                         source: _,
                         config_dir,
                         environment: _,
+                        install_options: _,
+                        version: _,
                     }
                     | Package::Custom {
                         source: _,
                         local_dir: _,
+                        build: _,
+                        install: _,
+                        config_dir,
+                    }
+                    | Package::GitHubRelease {
+                        owner: _,
+                        repo: _,
+                        asset_pattern: _,
+                        bin: _,
+                        checksum_asset: _,
+                        version: _,
+                        config_dir,
+                    }
+                    | Package::Archive {
+                        url_template: _,
+                        version: _,
+                        sha256: _,
+                        strip_components: _,
+                        bins: _,
+                        config_dir,
+                    }
+                    | Package::PythonTool {
+                        package_name: _,
+                        installer: _,
+                        version: _,
+                        config_dir,
+                    }
+                    | Package::NpmPackage {
+                        package_name: _,
+                        version: _,
+                        config_dir,
+                    }
+                    | Package::System {
+                        apt: _,
+                        dnf: _,
+                        pacman: _,
+                        mode: _,
+                        config_dir,
+                    }
+                    | Package::GoPackage {
+                        package_path: _,
+                        module: _,
+                        version: _,
+                        config_dir,
+                    }
+                    | Package::Plugin {
+                        provider: _,
+                        args: _,
+                        config_dir,
+                    }
+                    | Package::Extension {
+                        provider: _,
+                        args: _,
                         config_dir,
                     } => match config_dir {
                         Some(ConfigDir::User {
                             package_name,
                             filter,
+                            priority,
                         }) => ret.add_dir(
                             &owner,
                             &self
@@ -100,39 +193,103 @@ impl Logix {
                                 .user_config()
                                 .make_shadowed_subdir(package_name.as_deref().unwrap_or(pname))?,
                             filter.as_ref().unwrap_or(Filter::EMPTY),
+                            priority.unwrap_or(DEFAULT_PRIORITY),
                         )?,
                         None => {}
                     },
                 }
             }
         }
-        Ok(ret.finalize())
+        ret.finalize()
     }
 
-    /// Calculate the status of all the config files managed by logix
+    /// Calculate the status of all the config files managed by logix, the content
+    /// hashes are kept in an index in the logix cache to speed up later runs.
+    ///
+    /// The files are processed in parallel, but returned in a deterministic order
     pub fn calculate_config_status(
         &self,
     ) -> Result<impl ExactSizeIterator<Item = (FileStatus, ManagedFile)>, Error> {
+        let index = self.load_file_index()?;
+        let ret = self.calculate_owned_config_status(&index, |_| true)?;
+        index.save();
+        Ok(ret.into_iter())
+    }
+
+    /// Calculate the status of the config files managed by the package `name`
+    pub fn calculate_package_config_status(
+        &self,
+        name: &str,
+    ) -> Result<impl ExactSizeIterator<Item = (FileStatus, ManagedFile)>, Error> {
+        // NOTE: The index is not saved as it would only keep the files of the package
+        let index = self.load_file_index()?;
+        let ret = self.calculate_owned_config_status(&index, |owner| match owner {
+            Owner::Package(owner) => **owner == *name,
+            Owner::Ssh | Owner::Shell => false,
+        })?;
+        Ok(ret.into_iter())
+    }
+
+    fn load_file_index(&self) -> Result<FileIndex, Error> {
+        Ok(FileIndex::load(
+            self.env.user_cache().join("logix/file-index.json")?,
+        ))
+    }
+
+    fn calculate_owned_config_status(
+        &self,
+        index: &FileIndex,
+        f: impl Fn(&Owner) -> bool + Sync,
+    ) -> Result<Vec<(FileStatus, ManagedFile)>, Error> {
         Ok(self
             .calculate_managed_files()?
-            .into_iter()
-            .map(|file| (file.calculate_status(), file)))
+            .into_par_iter()
+            .filter(|file| f(file.owner()))
+            .map(|file| (file.calculate_status(index), file))
+            .collect())
     }
 
-    pub fn iter_packages(&self) -> impl ExactSizeIterator<Item = ManagedPackage> {
+    pub fn iter_packages(&self) -> impl ExactSizeIterator<Item = ManagedPackage<'_>> {
+        self.config.home.packages.iter().map(|(name, info)| {
+            ManagedPackage::new(name, info, self.package_defaults(), self.lockfile.get(name))
+        })
+    }
+
+    pub fn find_package(&self, name: &str) -> Option<ManagedPackage<'_>> {
         self.config
             .home
             .packages
-            .iter()
-            .map(|(name, info)| ManagedPackage::new(name, info))
+            .get_key_value(name)
+            .map(|(name, info)| {
+                ManagedPackage::new(name, info, self.package_defaults(), self.lockfile.get(name))
+            })
     }
 
-    pub fn find_package(&self, name: &str) -> Option<ManagedPackage> {
+    /// Returns the packages installed by logix that are no longer declared in
+    /// the config, including packages declared with another kind than when installed.
+    /// Packages are matched by how they are installed, so renaming a package in
+    /// the config doesn't make it an orphan. Fails if how a declared package is
+    /// installed is unknown, as its record could otherwise be taken for an orphan
+    pub fn find_orphans<'a>(
+        &self,
+        state: &'a SystemState,
+    ) -> Result<Vec<&'a InstalledPackage>, Error> {
+        let declared = self
+            .iter_packages()
+            .map(|package| package.installed_kind(state))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(state
+            .installed_packages()
+            .filter(|installed| !declared.contains(&installed.kind))
+            .collect())
+    }
+
+    fn package_defaults(&self) -> &config::PackageDefaults {
         self.config
             .home
-            .packages
-            .get_key_value(name)
-            .map(|(name, info)| ManagedPackage::new(name, info))
+            .package_defaults
+            .as_ref()
+            .unwrap_or(config::PackageDefaults::EMPTY)
     }
 }
 
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{based_path::BasedPath, error::Error, managed_package::PackageVersion};
+
+/// Bump this when the format of the lockfile changes in an incompatible way
+const LOCKFILE_VERSION: u32 = 1;
+
+/// The name of the lockfile, stored next to `root.logix`
+pub const LOCKFILE_NAME: &str = "logix.lock";
+
+#[derive(Serialize, Deserialize)]
+struct LockfileData {
+    version: u32,
+    packages: BTreeMap<String, PackageVersion>,
+}
+
+/// The resolved versions of the packages, making it possible to install the
+/// same versions on several machines
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Lockfile {
+    packages: BTreeMap<String, PackageVersion>,
+}
+
+impl Lockfile {
+    /// Load the lockfile at `path`, a missing lockfile is treated as empty
+    pub fn load(path: &BasedPath) -> Result<Self, Error> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(Error::ReadLockfile(path.clone(), e.to_string())),
+        };
+
+        let data: LockfileData = serde_json::from_slice(&data)
+            .map_err(|e| Error::ReadLockfile(path.clone(), e.to_string()))?;
+
+        if data.version != LOCKFILE_VERSION {
+            return Err(Error::ReadLockfile(
+                path.clone(),
+                format!(
+                    "unsupported version {} (want {LOCKFILE_VERSION})",
+                    data.version
+                ),
+            ));
+        }
+
+        Ok(Self {
+            packages: data.packages,
+        })
+    }
+
+    /// Write the lockfile to `path`
+    pub fn save(&self, path: &BasedPath) -> Result<(), Error> {
+        let data = LockfileData {
+            version: LOCKFILE_VERSION,
+            packages: self.packages.clone(),
+        };
+
+        let mut json = serde_json::to_string_pretty(&data)
+            .map_err(|e| Error::WriteLockfile(path.clone(), e.to_string()))?;
+        json.push('\n');
+
+        let tmp_path = path.with_extension("lock.tmp");
+        std::fs::write(&tmp_path, json)
+            .and_then(|()| std::fs::rename(&tmp_path, path))
+            .map_err(|e| Error::WriteLockfile(path.clone(), e.to_string()))
+    }
+
+    /// Get the locked version of the package
+    pub fn get(&self, name: &str) -> Option<&PackageVersion> {
+        self.packages.get(name)
+    }
+
+    /// Lock the package to the specified version, returns the previously locked version
+    pub fn set(&mut self, name: &str, version: PackageVersion) -> Option<PackageVersion> {
+        self.packages.insert(name.into(), version)
+    }
+
+    /// Remove packages that no longer exist in the config
+    pub fn retain(&mut self, mut keep: impl FnMut(&str) -> bool) {
+        self.packages.retain(|name, _| keep(name))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PackageVersion)> {
+        self.packages.iter().map(|(k, v)| (k.as_str(), v))
+    }
+}
@@ -3,10 +3,14 @@
 use std::path::PathBuf;
 
 use logix::{
+    based_path::BasedPath,
     config::Shell,
     error::Error,
-    managed_file::{FileStatus, LocalFile, ManagedFile},
-    managed_package::{ManagedPackage, PackageStatus, PackageVersion},
+    managed_file::{FileKind, FileStatus, LocalFile, ManagedFile},
+    managed_package::{
+        ChangedCommit, ChangedRelease, ManagedPackage, PackageChanges, PackageDetails,
+        PackageStatus, PackageVersion, UpdateStatus,
+    },
     system_state::SystemState,
 };
 
@@ -51,9 +55,29 @@ enum Command {
         /// Get more detailed status about the specified package
         #[clap(long, short = 'p')]
         package: Option<String>,
+        /// List the commits or releases between the installed and the target version
+        #[clap(long, requires = "package")]
+        changes: bool,
     },
     UpdateConfig {},
-    InstallUpdates {},
+    /// Install or update packages, the versions locked in `logix.lock` are installed exactly
+    InstallUpdates {
+        /// Record packages that were installed before logix managed them as
+        /// installed by logix, so that `prune` uninstalls them once they are
+        /// removed from the config
+        #[clap(long)]
+        adopt: bool,
+    },
+    /// Uninstall packages installed by logix that are no longer in the config
+    Prune {},
+    /// Record the resolved package versions in `logix.lock`
+    Lock {
+        /// Update the locked versions to the latest allowed versions, by
+        /// default only packages that are not locked yet, or that are locked
+        /// to another kind of version than the source resolves, are added
+        #[clap(long)]
+        update: bool,
+    },
     NewConfig {
         #[clap(short = 'u', long)]
         username: String,
@@ -112,38 +136,226 @@ impl Context {
 
         writeln!(
             self,
-            "{:<20}  {:<16}  {:<16}  {:<16}",
+            "{:<20}  {:<16}  {:<16}  {:<16}  {:<16}  {:<16}  {:<16}",
             "Name".color(self.theme.status_header),
+            "Status".color(self.theme.status_header),
             "Installed".color(self.theme.status_header),
             "Downloaded".color(self.theme.status_header),
+            "Locked".color(self.theme.status_header),
             "Remote".color(self.theme.status_header),
+            "Available".color(self.theme.status_header),
         );
 
-        for package in it {
+        let packages = it.collect::<Vec<_>>();
+        let statuses = ManagedPackage::calculate_status_of_all(&state, &packages);
+
+        for (package, status) in packages.iter().zip(statuses) {
+            let status = status?;
+            let not_built = status.is_downloaded_not_built();
             let PackageStatus {
                 installed_version,
                 downloaded_version,
                 latest_version,
-            } = package.calculate_status(&state)?;
+                available_version,
+                locked_version,
+                update_status,
+                warnings,
+            } = status;
             writeln!(
                 self,
-                " {:<20}  {:<16}  {:<16}  {:<16}",
+                " {:<20}  {:<16}  {:<16}  {:<16}  {:<16}  {:<16}  {:<16}",
                 package.name().color(self.theme.owner_package),
+                colored::update_status(update_status, &self.theme),
                 colored::package_version(&installed_version, &self.theme),
                 colored::package_version(&downloaded_version, &self.theme),
+                colored::package_version(&locked_version, &self.theme),
                 colored::package_version(&latest_version, &self.theme),
+                colored::package_version(&available_version, &self.theme),
             );
+            if not_built {
+                writeln!(
+                    self,
+                    "   {} the downloaded sources have not been built and installed",
+                    "note:".color(self.theme.package_not_built)
+                );
+            }
+            for warning in warnings {
+                writeln!(
+                    self,
+                    "   {} {warning}",
+                    "warning:".color(self.theme.package_warning)
+                );
+            }
         }
         writeln!(self);
         Ok(())
     }
 
     fn packages_status(&self) -> Result<(), Error> {
-        self.print_packages_status(self.logix.iter_packages())
+        self.print_packages_status(self.logix.iter_packages())?;
+        self.orphans_status()
     }
 
     fn package_status(&self, name: &str) -> Result<(), Error> {
-        self.print_packages_status(self.logix.find_package(name).into_iter())
+        let Some(package) = self.logix.find_package(name) else {
+            return Err(Error::UnknownPackage(name.into()));
+        };
+        let state = SystemState::init(self.logix.env())?;
+        let status = package.calculate_status(&state)?;
+        let PackageDetails {
+            source,
+            location,
+            bins,
+            environment,
+        } = package.details(&state)?;
+
+        let unknown = "<unknown>".color(self.theme.package_version_none);
+        let header = |name: &str| {
+            format!("{name:<12}")
+                .color(self.theme.status_header)
+                .to_string()
+        };
+
+        writeln!(
+            self,
+            "Package {}",
+            package.name().color(self.theme.owner_package)
+        );
+        match source {
+            Some(source) => writeln!(self, " {}  {source}", header("Source")),
+            None => writeln!(self, " {}  {unknown}", header("Source")),
+        }
+        match location {
+            Some(location) => writeln!(self, " {}  {}", header("Location"), location.display()),
+            None => writeln!(self, " {}  {unknown}", header("Location")),
+        }
+        if bins.is_empty() {
+            writeln!(self, " {}  {unknown}", header("Binaries"));
+        }
+        for (i, bin) in bins.iter().enumerate() {
+            let name = if i == 0 { "Binaries" } else { "" };
+            writeln!(self, " {}  {}", header(name), bin.display());
+        }
+        for (i, (key, value)) in environment.iter().enumerate() {
+            let name = if i == 0 { "Environment" } else { "" };
+            writeln!(self, " {}  {key}={value}", header(name));
+        }
+        writeln!(self);
+
+        writeln!(
+            self,
+            " {}  {}",
+            header("Status"),
+            colored::update_status(status.update_status, &self.theme)
+        );
+        let not_built = status.is_downloaded_not_built();
+        for (name, version) in [
+            ("Installed", &status.installed_version),
+            ("Downloaded", &status.downloaded_version),
+            ("Locked", &status.locked_version),
+            ("Remote", &status.latest_version),
+            ("Available", &status.available_version),
+        ] {
+            writeln!(
+                self,
+                " {}  {}",
+                header(name),
+                colored::package_version(version, &self.theme)
+            );
+        }
+        if not_built {
+            writeln!(
+                self,
+                "   {} the downloaded sources have not been built and installed",
+                "note:".color(self.theme.package_not_built)
+            );
+        }
+        for warning in &status.warnings {
+            writeln!(
+                self,
+                "   {} {warning}",
+                "warning:".color(self.theme.package_warning)
+            );
+        }
+        writeln!(self);
+
+        writeln!(
+            self,
+            "{:<15}  {}",
+            "Status".color(self.theme.status_header),
+            "Config file".color(self.theme.status_header),
+        );
+        for (status, file) in self.logix.calculate_package_config_status(name)? {
+            let status = colored::status(status, &self.theme);
+            let local = colored::path(file.local_path(), &self.theme.local_file);
+            writeln!(self, " {status:<15}  {local}");
+        }
+        writeln!(self);
+        Ok(())
+    }
+
+    fn package_changes(&self, name: &str) -> Result<(), Error> {
+        let Some(package) = self.logix.find_package(name) else {
+            return Err(Error::UnknownPackage(name.into()));
+        };
+        let state = SystemState::init(self.logix.env())?;
+        let status = package.calculate_status(&state)?;
+
+        if !status.need_update() {
+            writeln!(
+                self,
+                "Package {} is up to date",
+                package.name().color(self.theme.owner_package),
+            );
+            return Ok(());
+        }
+
+        writeln!(
+            self,
+            "Changes from {} to {}:",
+            colored::package_version(&status.installed_version, &self.theme),
+            colored::package_version(status.target_version(), &self.theme),
+        );
+        match package.changes(&state, &status)? {
+            Some(PackageChanges::Commits(commits)) => {
+                for ChangedCommit {
+                    id,
+                    summary,
+                    author,
+                    date,
+                } in commits
+                {
+                    let date = date.map(|v| v.strftime("%Y-%m-%d").to_string());
+                    writeln!(
+                        self,
+                        " {:<10}  {:<10}  {:<20}  {summary}",
+                        (&id[..id.len().min(10)]).color(self.theme.package_version_str),
+                        date.unwrap_or_default()
+                            .color(self.theme.package_version_date),
+                        author.color(self.theme.owner_package),
+                    );
+                }
+            }
+            Some(PackageChanges::Releases(releases)) => {
+                for ChangedRelease {
+                    version: _,
+                    tag,
+                    notes,
+                } in releases
+                {
+                    writeln!(self, " {}", tag.color(self.theme.package_version_str));
+                    for line in notes.lines() {
+                        writeln!(self, "   {line}");
+                    }
+                    writeln!(self);
+                }
+            }
+            None => {
+                writeln!(self, " The changes are not known for this package");
+            }
+        }
+        writeln!(self);
+        Ok(())
     }
 
     fn update_config(&self) -> Result<(), Error> {
@@ -170,29 +382,95 @@ impl Context {
                         diff_text_files(self, &local, &logix)?;
                     }
                     ManagedFile::Virtual(_, _) => todo!(),
+                    ManagedFile::Unreadable(_, _) => unreachable!(),
                 },
-                FileStatus::ErrorReadingLocal(_) => todo!(),
-                FileStatus::ErrorReadingLogix(_) => todo!(),
+                FileStatus::LinkDiffers => {
+                    let describe = |path: Option<&BasedPath>| match path.map(|p| FileKind::read(p))
+                    {
+                        Some(Ok(Some(FileKind::Link(target)))) => format!("link to {target:?}"),
+                        Some(Ok(Some(FileKind::File))) => "regular file".into(),
+                        Some(Ok(None)) | None => "missing".into(),
+                        Some(Err(e)) => format!("error: {e}"),
+                    };
+                    writeln!(self, "Config link differs");
+                    writeln!(
+                        self,
+                        "Current config: {} ({})",
+                        colored::path(file.local_path(), &self.theme.local_file),
+                        describe(file.local_path()),
+                    );
+                    writeln!(
+                        self,
+                        "Logix config:   {}/{} ({})",
+                        ".config/logix".color(self.theme.logix_root), // TODO: Need to be dynamic
+                        colored::path(file.logix_path(), &self.theme.logix_file),
+                        describe(file.logix_path()),
+                    );
+                    writeln!(self);
+                }
+                FileStatus::ErrorReadingLocal(kind) => {
+                    writeln!(
+                        self,
+                        "Skipping {} due to error: {kind}",
+                        colored::path(file.local_path(), &self.theme.local_file)
+                    );
+                }
+                FileStatus::ErrorReadingLogix(kind) => {
+                    writeln!(
+                        self,
+                        "Skipping {} due to error: {kind}",
+                        colored::path(file.logix_path(), &self.theme.logix_file)
+                    );
+                }
             }
         }
         Ok(())
     }
 
-    pub fn install_updates(&self) -> Result<(), Error> {
+    pub fn install_updates(&self, adopt: bool) -> Result<(), Error> {
         let mut state = SystemState::init(self.logix.env())?;
         for package in self.logix.iter_packages() {
-            if package.is_custom() {
-                // TODO: Add support for custom packages
-                continue;
-            }
-
             let status = package.calculate_status(&state)?;
-            if status.need_update() {
+            let target = status.target_version();
+            if status.need_update() && package.is_check_only(&state) {
+                writeln!(
+                    self,
+                    "Skipping package {} as it is check-only, install version {} using {}",
+                    package.name().color(self.theme.owner_package),
+                    colored::package_version(target, &self.theme),
+                    state
+                        .system_package_manager()
+                        .map_or_else(|| "the package manager".into(), |v| v.to_string()),
+                );
+            } else if status.need_update() {
                 if matches!(status.installed_version, PackageVersion::None) {
                     writeln!(
                         self,
                         "Installing version {} of package {}",
-                        colored::package_version(&status.latest_version, &self.theme),
+                        colored::package_version(target, &self.theme),
+                        package.name().color(self.theme.owner_package),
+                    );
+                } else if status.update_status == UpdateStatus::AheadOfRemote {
+                    writeln!(
+                        self,
+                        "Downgrading package {} from {} to the locked version {}",
+                        package.name().color(self.theme.owner_package),
+                        colored::package_version(&status.installed_version, &self.theme),
+                        colored::package_version(target, &self.theme),
+                    );
+                } else if status.update_status == UpdateStatus::Changed {
+                    writeln!(
+                        self,
+                        "Replacing version {} of package {} with {}, the versions can't be ordered",
+                        colored::package_version(&status.installed_version, &self.theme),
+                        package.name().color(self.theme.owner_package),
+                        colored::package_version(target, &self.theme),
+                    );
+                } else if status.installed_version.is_same(target) {
+                    writeln!(
+                        self,
+                        "Reinstalling version {} of package {} to match the config",
+                        colored::package_version(&status.installed_version, &self.theme),
                         package.name().color(self.theme.owner_package),
                     );
                 } else {
@@ -201,15 +479,152 @@ impl Context {
                         "Updating package {} from {} to {}",
                         package.name().color(self.theme.owner_package),
                         colored::package_version(&status.installed_version, &self.theme),
-                        colored::package_version(&status.latest_version, &self.theme),
+                        colored::package_version(target, &self.theme),
                     );
                 }
 
-                package.install_update(&mut state)?;
+                package.install_update(&mut state, target)?;
+            } else if adopt
+                && status.installed_version != PackageVersion::None
+                && !package.is_check_only(&state)
+            {
+                // NOTE: Only recorded on request, as logix didn't install the package
+                let kind = package.installed_kind(&state)?;
+                if state.installed_package(package.name(), &kind).is_some() {
+                    continue;
+                }
+                writeln!(
+                    self,
+                    "Adopting version {} of package {}",
+                    colored::package_version(&status.installed_version, &self.theme),
+                    package.name().color(self.theme.owner_package),
+                );
+                package.record_installed(&mut state, &status.installed_version)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn orphans_status(&self) -> Result<(), Error> {
+        let state = SystemState::init(self.logix.env())?;
+        let orphans = self
+            .logix
+            .find_orphans(&state)?
+            .into_iter()
+            .filter(|orphan| state.is_installed(&orphan.kind))
+            .collect::<Vec<_>>();
+
+        if orphans.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(
+            self,
+            "Packages no longer in the config, run {} to uninstall them:",
+            "logix prune".color(self.theme.owner_package),
+        );
+        for orphan in orphans {
+            writeln!(
+                self,
+                " {:<20}  {:<16}",
+                orphan.name.color(self.theme.owner_package),
+                colored::package_version(&orphan.version, &self.theme),
+            );
+        }
+        writeln!(self);
+        Ok(())
+    }
+
+    pub fn prune(&self) -> Result<(), Error> {
+        let mut state = SystemState::init(self.logix.env())?;
+        let orphans = self
+            .logix
+            .find_orphans(&state)?
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for orphan in orphans {
+            if state.is_installed(&orphan.kind) {
+                writeln!(
+                    self,
+                    "Uninstalling package {}",
+                    orphan.name.color(self.theme.owner_package),
+                );
+            } else {
+                writeln!(
+                    self,
+                    "Forgetting package {} as it is no longer installed",
+                    orphan.name.color(self.theme.owner_package),
+                );
+            }
+            // NOTE: The record is kept when it fails, so it is retried by the next prune
+            if let Err(e) = state.uninstall(&orphan.kind) {
+                writeln!(
+                    self,
+                    "Failed to uninstall package {}: {e}",
+                    orphan.name.color(self.theme.owner_package),
+                );
             }
         }
         Ok(())
     }
+
+    pub fn lock(&self, update: bool) -> Result<(), Error> {
+        let state = SystemState::init(self.logix.env())?;
+        let mut lockfile = self.logix.lockfile().clone();
+
+        lockfile.retain(|name| {
+            let keep = self.logix.find_package(name).is_some();
+            if !keep {
+                writeln!(
+                    self,
+                    "Removing package {} from the lockfile",
+                    name.color(self.theme.owner_package),
+                );
+            }
+            keep
+        });
+
+        let packages = self.logix.iter_packages().collect::<Vec<_>>();
+        let statuses = ManagedPackage::calculate_status_of_all(&state, &packages);
+
+        for (package, status) in packages.iter().zip(statuses) {
+            let status = status?;
+            // NOTE: A lock of the wrong kind is ignored by the status, so it is resolved again
+            if !update && status.locked_version != PackageVersion::None {
+                continue;
+            }
+            let version = status.latest_version;
+            if version == PackageVersion::None {
+                writeln!(
+                    self,
+                    "Skipping package {} as no version was found",
+                    package.name().color(self.theme.owner_package),
+                );
+                continue;
+            }
+
+            match lockfile.set(package.name(), version.clone()) {
+                Some(prev) if prev == version => {}
+                Some(prev) => writeln!(
+                    self,
+                    "Updating lock of package {} from {} to {}",
+                    package.name().color(self.theme.owner_package),
+                    colored::package_version(&prev, &self.theme),
+                    colored::package_version(&version, &self.theme),
+                ),
+                None => writeln!(
+                    self,
+                    "Locking package {} to {}",
+                    package.name().color(self.theme.owner_package),
+                    colored::package_version(&version, &self.theme),
+                ),
+            }
+        }
+
+        lockfile.save(&self.logix.lockfile_path()?)
+    }
 }
 
 fn main() -> logix::error::Result<()> {
@@ -232,6 +647,16 @@ fn main() -> logix::error::Result<()> {
 
     logger.start().unwrap();
 
+    // NOTE: Calculating the status of packages is mostly waiting on cargo and
+    // the network, so use more threads than there are cores
+    let num_threads = std::thread::available_parallelism().map_or(16, |v| v.get().max(16));
+    if let Err(e) = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build_global()
+    {
+        log::warn!("Failed to configure the thread pool: {e}");
+    }
+
     let theme = Theme::default_term();
 
     match command {
@@ -248,10 +673,13 @@ fn main() -> logix::error::Result<()> {
             let ctx = Context::load(theme, shared)?;
             ctx.config_status()?;
         }
-        Command::PackageStatus { package } => {
+        Command::PackageStatus { package, changes } => {
             let ctx = Context::load(theme, shared)?;
             if let Some(package) = package {
                 ctx.package_status(&package)?;
+                if changes {
+                    ctx.package_changes(&package)?;
+                }
             } else {
                 ctx.packages_status()?;
             }
@@ -260,9 +688,17 @@ fn main() -> logix::error::Result<()> {
             let ctx = Context::load(theme, shared)?;
             ctx.update_config()?;
         }
-        Command::InstallUpdates {} => {
+        Command::InstallUpdates { adopt } => {
+            let ctx = Context::load(theme, shared)?;
+            ctx.install_updates(adopt)?;
+        }
+        Command::Prune {} => {
+            let ctx = Context::load(theme, shared)?;
+            ctx.prune()?;
+        }
+        Command::Lock { update } => {
             let ctx = Context::load(theme, shared)?;
-            ctx.install_updates()?;
+            ctx.lock(update)?;
         }
         Command::NewConfig {
             ref username,
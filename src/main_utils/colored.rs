@@ -3,7 +3,7 @@ use std::fmt;
 use logix::{
     based_path::BasedPath,
     managed_file::{FileStatus, Owner},
-    managed_package::PackageVersion,
+    managed_package::{PackageVersion, UpdateStatus},
 };
 use owo_colors::{AnsiColors, DynColor, OwoColorize};
 
@@ -82,11 +82,24 @@ pub fn status(status: FileStatus, theme: &Theme) -> impl fmt::Display {
         FileStatus::LocalAdded => "Missing logix".color(theme.status_local_added),
         FileStatus::LogixAdded => "Missing local".color(theme.status_logix_added),
         FileStatus::Modified => "Has changes".color(theme.status_modified),
+        FileStatus::LinkDiffers => "Link differs".color(theme.status_modified),
         FileStatus::ErrorReadingLocal(_) => "Local error".color(theme.status_error),
         FileStatus::ErrorReadingLogix(_) => "Logix error".color(theme.status_error),
     }
 }
 
+pub fn update_status(status: UpdateStatus, theme: &Theme) -> impl fmt::Display {
+    match status {
+        UpdateStatus::NotInstalled => "Not installed".color(theme.status_error),
+        UpdateStatus::UpToDate => "Up to date".color(theme.status_up_to_date),
+        UpdateStatus::Outdated => "Outdated".color(theme.status_modified),
+        UpdateStatus::AheadOfRemote => "Ahead of remote".color(theme.status_modified),
+        UpdateStatus::Diverged => "Diverged".color(theme.status_error),
+        UpdateStatus::Changed => "Changed".color(theme.status_modified),
+        UpdateStatus::Unknown => "Unknown".color(theme.package_version_none),
+    }
+}
+
 pub fn path<'a>(path: Option<&'a BasedPath>, color: &'a OptColor) -> impl fmt::Display + 'a {
     struct ColoredPath<'a> {
         path: Option<&'a BasedPath>,
@@ -141,13 +154,23 @@ pub fn package_version<'a>(
                 PackageVersion::None => {
                     fmt::Display::fmt(&"<none>".color(self.theme.package_version_none), f)
                 }
-                PackageVersion::Commit { id: _, date } => {
+                PackageVersion::Commit {
+                    id: _,
+                    date: Some(date),
+                } => {
                     let tmp = date.strftime("%Y-%m-%d %H:%M");
                     fmt::Display::fmt(&tmp.color(self.theme.package_version_date), f)
                 }
+                PackageVersion::Commit { id, date: None } => {
+                    let short = &id[..id.len().min(10)];
+                    fmt::Display::fmt(&short.color(self.theme.package_version_date), f)
+                }
                 PackageVersion::Semver(ver) => {
                     fmt::Display::fmt(&ver.color(self.theme.package_version_str), f)
                 }
+                PackageVersion::Other(ver) => {
+                    fmt::Display::fmt(&ver.color(self.theme.package_version_str), f)
+                }
             }
         }
     }
@@ -26,6 +26,8 @@ pub struct Theme {
     pub package_version_none: Color,
     pub package_version_date: Color,
     pub package_version_str: Color,
+    pub package_warning: Color,
+    pub package_not_built: Color,
     pub diff: DiffTheme,
 }
 
@@ -69,6 +71,8 @@ impl Theme {
             package_version_none: dimmed_blue,
             package_version_date: white_blue,
             package_version_str: bright_blue,
+            package_warning: yellow,
+            package_not_built: yellow,
             diff: DiffTheme {
                 removed: red,
                 added: green,
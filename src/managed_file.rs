@@ -1,6 +1,9 @@
-use std::sync::Arc;
+use std::{fmt, path::Path, path::PathBuf, sync::Arc};
 
-use crate::based_path::BasedPath;
+use crate::{
+    based_path::BasedPath,
+    file_index::{ContentHash, FileIndex},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Owner {
@@ -9,6 +12,16 @@ pub enum Owner {
     Package(Arc<str>),
 }
 
+impl fmt::Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ssh => write!(f, "ssh"),
+            Self::Shell => write!(f, "shell"),
+            Self::Package(name) => write!(f, "package {name:?}"),
+        }
+    }
+}
+
 /// Represents the status of a given file
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum FileStatus {
@@ -17,10 +30,34 @@ pub enum FileStatus {
     LocalAdded,
     LogixAdded,
     Modified,
+    /// At least one of the files is a symlink, and they do not point to the same target
+    LinkDiffers,
     ErrorReadingLocal(std::io::ErrorKind),
     ErrorReadingLogix(std::io::ErrorKind),
 }
 
+/// The kind of a file in a managed directory, symlinks are not followed
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub enum FileKind {
+    File,
+    /// A symlink and the path it points to
+    Link(PathBuf),
+}
+
+impl FileKind {
+    /// Read the kind of the file at `path`, returns `None` if it doesn't exist
+    pub fn read(path: &Path) -> std::io::Result<Option<Self>> {
+        match std::fs::symlink_metadata(path) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                Ok(Some(Self::Link(std::fs::read_link(path)?)))
+            }
+            Ok(_) => Ok(Some(Self::File)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub struct LocalFile {
     pub local: BasedPath,
@@ -28,15 +65,23 @@ pub struct LocalFile {
 }
 
 impl LocalFile {
-    fn calculate_status(&self) -> FileStatus {
+    fn calculate_status(&self, index: &FileIndex) -> FileStatus {
         let Self { local, logix } = self;
-        if local.exists() {
-            if logix.exists() {
-                let a = match std::fs::read(local) {
+        let local_kind = match FileKind::read(local) {
+            Ok(kind) => kind,
+            Err(e) => return FileStatus::ErrorReadingLocal(e.kind()),
+        };
+        let logix_kind = match FileKind::read(logix) {
+            Ok(kind) => kind,
+            Err(e) => return FileStatus::ErrorReadingLogix(e.kind()),
+        };
+        match (local_kind, logix_kind) {
+            (Some(FileKind::File), Some(FileKind::File)) => {
+                let a = match index.hash_file(local) {
                     Ok(a) => a,
                     Err(e) => return FileStatus::ErrorReadingLocal(e.kind()),
                 };
-                let b = match std::fs::read(logix) {
+                let b = match index.hash_file(logix) {
                     Ok(b) => b,
                     Err(e) => return FileStatus::ErrorReadingLogix(e.kind()),
                 };
@@ -45,13 +90,18 @@ impl LocalFile {
                 } else {
                     FileStatus::Modified
                 }
-            } else {
-                FileStatus::LocalAdded
             }
-        } else if logix.exists() {
-            FileStatus::LogixAdded
-        } else {
-            FileStatus::MissingFromBoth
+            (Some(FileKind::Link(a)), Some(FileKind::Link(b))) => {
+                if a == b {
+                    FileStatus::UpToDate
+                } else {
+                    FileStatus::LinkDiffers
+                }
+            }
+            (Some(_), Some(_)) => FileStatus::LinkDiffers,
+            (Some(_), None) => FileStatus::LocalAdded,
+            (None, Some(_)) => FileStatus::LogixAdded,
+            (None, None) => FileStatus::MissingFromBoth,
         }
     }
 }
@@ -63,14 +113,14 @@ pub struct VirtualFile {
 }
 
 impl VirtualFile {
-    fn calculate_status(&self) -> FileStatus {
+    fn calculate_status(&self, index: &FileIndex) -> FileStatus {
         let Self { local, content } = self;
         if local.exists() {
-            let a = match std::fs::read(local) {
+            let a = match index.hash_file(local) {
                 Ok(a) => a,
                 Err(e) => return FileStatus::ErrorReadingLocal(e.kind()),
             };
-            if a == content.as_bytes() {
+            if a == ContentHash::of_bytes(content.as_bytes()) {
                 FileStatus::UpToDate
             } else {
                 FileStatus::Modified
@@ -81,17 +131,28 @@ impl VirtualFile {
     }
 }
 
+/// A file or directory that could not be visited when walking a managed directory
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct UnreadableFile {
+    pub file: LocalFile,
+    /// Either [FileStatus::ErrorReadingLocal] or [FileStatus::ErrorReadingLogix]
+    pub status: FileStatus,
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Hash, Clone)]
 pub enum ManagedFile {
     Local(Owner, LocalFile),
     Virtual(Owner, VirtualFile),
+    Unreadable(Owner, UnreadableFile),
 }
 
 impl ManagedFile {
-    pub fn calculate_status(&self) -> FileStatus {
+    /// Calculate the status of the file, using `index` to avoid reading unchanged files
+    pub fn calculate_status(&self, index: &FileIndex) -> FileStatus {
         match self {
-            Self::Local(_, file) => file.calculate_status(),
-            Self::Virtual(_, file) => file.calculate_status(),
+            Self::Local(_, file) => file.calculate_status(index),
+            Self::Virtual(_, file) => file.calculate_status(index),
+            Self::Unreadable(_, file) => file.status,
         }
     }
 
@@ -99,6 +160,7 @@ impl ManagedFile {
         match self {
             Self::Local(_, file) => Some(&file.local),
             Self::Virtual(_, file) => Some(&file.local),
+            Self::Unreadable(_, file) => Some(&file.file.local),
         }
     }
 
@@ -106,12 +168,13 @@ impl ManagedFile {
         match self {
             Self::Local(_, file) => Some(&file.logix),
             Self::Virtual(_, _) => None,
+            Self::Unreadable(_, file) => Some(&file.file.logix),
         }
     }
 
     pub fn owner(&self) -> &Owner {
         match self {
-            Self::Local(owner, _) | Self::Virtual(owner, _) => owner,
+            Self::Local(owner, _) | Self::Virtual(owner, _) | Self::Unreadable(owner, _) => owner,
         }
     }
 }
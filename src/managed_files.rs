@@ -1,16 +1,22 @@
-use std::path::Path;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    path::{Path, PathBuf},
+};
 
 use crate::{
     config::Filter,
     env::{Env, ShadowedDir},
     error::Error,
-    managed_file::{LocalFile, ManagedFile, Owner},
+    managed_file::{FileStatus, LocalFile, ManagedFile, Owner, UnreadableFile},
     walk_dir::{walk_dirs, WalkEntry},
 };
 
+/// The priority used for files that does not specify one, see [ManagedFiles::finalize]
+pub const DEFAULT_PRIORITY: i32 = 0;
+
 pub struct ManagedFiles<'env> {
     env: &'env Env,
-    files: Vec<ManagedFile>,
+    files: Vec<(i32, ManagedFile)>,
 }
 
 impl<'env> ManagedFiles<'env> {
@@ -21,9 +27,15 @@ impl<'env> ManagedFiles<'env> {
         }
     }
 
-    /// Add a [ManagedFile] to the list of files
+    /// Add a [ManagedFile] to the list of files using [DEFAULT_PRIORITY]
     pub fn add_file(&mut self, file: ManagedFile) {
-        self.files.push(file);
+        self.add_file_with_priority(file, DEFAULT_PRIORITY);
+    }
+
+    /// Add a [ManagedFile] to the list of files, the priority is used to
+    /// resolve conflicts in [ManagedFiles::finalize]
+    pub fn add_file_with_priority(&mut self, file: ManagedFile, priority: i32) {
+        self.files.push((priority, file));
     }
 
     /// Add the files from the given [ShadowedDirectory] using the specified [Filter]
@@ -32,10 +44,19 @@ impl<'env> ManagedFiles<'env> {
         owner: &Owner,
         dir: &ShadowedDir,
         local_filter: &Filter,
+        priority: i32,
     ) -> Result<(), Error> {
         walk_dirs(dir, local_filter, |entry| match entry {
             WalkEntry::Local(rel_path) | WalkEntry::Both(rel_path) | WalkEntry::Logix(rel_path) => {
-                self.add_local_file(owner, dir, rel_path)
+                self.add_local_file(owner, dir, rel_path, priority)
+            }
+            WalkEntry::LocalError(rel_path, kind) => {
+                let status = FileStatus::ErrorReadingLocal(kind);
+                self.add_unreadable_file(owner, dir, rel_path, status, priority)
+            }
+            WalkEntry::LogixError(rel_path, kind) => {
+                let status = FileStatus::ErrorReadingLogix(kind);
+                self.add_unreadable_file(owner, dir, rel_path, status, priority)
             }
         })
     }
@@ -45,11 +66,34 @@ impl<'env> ManagedFiles<'env> {
         owner: &Owner,
         dir: &ShadowedDir,
         rel_path: impl AsRef<Path>,
+        priority: i32,
     ) -> Result<(), Error> {
-        self.add_file(ManagedFile::Local(
-            owner.clone(),
-            dir.make_local_file(rel_path)?,
-        ));
+        self.add_file_with_priority(
+            ManagedFile::Local(owner.clone(), dir.make_local_file(rel_path)?),
+            priority,
+        );
+        Ok(())
+    }
+
+    /// Add a file that could not be read, it will show up with the specified status
+    fn add_unreadable_file(
+        &mut self,
+        owner: &Owner,
+        dir: &ShadowedDir,
+        rel_path: impl AsRef<Path>,
+        status: FileStatus,
+        priority: i32,
+    ) -> Result<(), Error> {
+        self.add_file_with_priority(
+            ManagedFile::Unreadable(
+                owner.clone(),
+                UnreadableFile {
+                    file: dir.make_local_file(rel_path)?,
+                    status,
+                },
+            ),
+            priority,
+        );
         Ok(())
     }
 
@@ -58,7 +102,7 @@ impl<'env> ManagedFiles<'env> {
         owner: &Owner,
         rel_path: impl AsRef<Path>,
     ) -> Result<(), Error> {
-        self.add_local_file(owner, self.env.user_config(), rel_path)
+        self.add_local_file(owner, self.env.user_config(), rel_path, DEFAULT_PRIORITY)
     }
 
     pub fn add_dotfile(&mut self, owner: &Owner, rel_path: impl AsRef<Path>) -> Result<(), Error> {
@@ -89,7 +133,51 @@ impl<'env> ManagedFiles<'env> {
         Ok(())
     }
 
-    pub(crate) fn finalize(self) -> Vec<ManagedFile> {
-        self.files
+    /// Returns the list of files, if several owners claims the same local file the
+    /// one with the highest priority is kept. It is an error if several owners
+    /// share the highest priority, the order the files were added doesn't matter.
+    pub(crate) fn finalize(self) -> Result<Vec<ManagedFile>, Error> {
+        let mut max_priority = HashMap::<PathBuf, i32>::new();
+        for (priority, file) in &self.files {
+            if let Some(path) = file.local_path() {
+                max_priority
+                    .entry(path.to_path_buf())
+                    .and_modify(|cur| *cur = (*cur).max(*priority))
+                    .or_insert(*priority);
+            }
+        }
+
+        // NOTE: Each path keeps the position of the first file claiming it
+        let mut ret = Vec::<Option<ManagedFile>>::with_capacity(self.files.len());
+        let mut by_path = HashMap::<PathBuf, usize>::new();
+
+        for (priority, file) in self.files {
+            let Some(path) = file.local_path() else {
+                ret.push(Some(file));
+                continue;
+            };
+            let is_max = max_priority[path.as_path()] == priority;
+
+            match by_path.entry(path.to_path_buf()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(ret.len());
+                    ret.push(is_max.then_some(file));
+                }
+                Entry::Occupied(_) if !is_max => {}
+                Entry::Occupied(entry) => match &ret[*entry.get()] {
+                    None => ret[*entry.get()] = Some(file),
+                    Some(cur_file) if *cur_file == file => {}
+                    Some(cur_file) => {
+                        return Err(Error::ConflictingOwners(
+                            path.clone(),
+                            cur_file.owner().clone(),
+                            file.owner().clone(),
+                        ))
+                    }
+                },
+            }
+        }
+
+        Ok(ret.into_iter().flatten().collect())
     }
 }
@@ -1,21 +1,284 @@
-use std::sync::Arc;
+use std::{fmt, path::PathBuf, sync::Arc};
 
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    config::Package, error::Error, github::GitHubRepo, helpers::cargo::CrateSpec,
+    config::{Package, PackageDefaults},
+    error::Error,
+    helpers::cargo::CrateFeatures,
+    installed_packages::{InstalledKind, InstalledPackage},
+    package_provider::PackageProvider,
     system_state::SystemState,
 };
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PackageVersion {
     /// The package does not exist
     None,
     Commit {
         id: String,
-        date: jiff::Timestamp,
+        /// The author date, unknown for plain git repositories
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        date: Option<jiff::Timestamp>,
     },
     Semver(semver::Version),
+    /// A version that can only be compared for equality, such as the version of a distro package
+    Other(String),
+}
+
+impl PackageVersion {
+    /// Compare two versions, commits are compared by id only as the date is
+    /// not known for all sources and the id may be abbreviated
+    pub fn is_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Commit { id: a, .. }, Self::Commit { id: b, .. }) => {
+                !a.is_empty()
+                    && !b.is_empty()
+                    && (a.starts_with(b.as_str()) || b.starts_with(a.as_str()))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Check if both versions are of the same kind, such as both being commits
+    pub fn is_same_kind(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Compare the version with `other`, the ordering only exists between
+    /// versions of the same kind, except that [PackageVersion::None] is older
+    /// than any version. Commits are ordered by their dates when both are known
+    pub fn compare(&self, other: &Self) -> VersionOrder {
+        match (self, other) {
+            (Self::None, Self::None) => VersionOrder::Same,
+            (Self::None, _) => VersionOrder::Older,
+            (_, Self::None) => VersionOrder::Newer,
+            (Self::Semver(a), Self::Semver(b)) => a.cmp(b).into(),
+            (a @ Self::Commit { .. }, b @ Self::Commit { .. }) if a.is_same(b) => {
+                VersionOrder::Same
+            }
+            (Self::Commit { date: Some(a), .. }, Self::Commit { date: Some(b), .. }) if a != b => {
+                a.cmp(b).into()
+            }
+            (Self::Other(a), Self::Other(b)) if a == b => VersionOrder::Same,
+            (Self::Commit { .. } | Self::Semver(_) | Self::Other(_), _) => VersionOrder::Unknown,
+        }
+    }
+}
+
+/// How a version compares to another version of the same package
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VersionOrder {
+    Same,
+    Older,
+    Newer,
+    /// Both versions have changes the other is missing, such as after a force push
+    Diverged,
+    /// The versions can't be compared, such as a commit and a semver version
+    Unknown,
+}
+
+impl From<std::cmp::Ordering> for VersionOrder {
+    fn from(ordering: std::cmp::Ordering) -> Self {
+        match ordering {
+            std::cmp::Ordering::Less => Self::Older,
+            std::cmp::Ordering::Equal => Self::Same,
+            std::cmp::Ordering::Greater => Self::Newer,
+        }
+    }
+}
+
+impl fmt::Display for PackageVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "<none>"),
+            Self::Commit { id, date: _ } => write!(f, "{id}"),
+            Self::Semver(version) => write!(f, "{version}"),
+            Self::Other(version) => write!(f, "{version}"),
+        }
+    }
+}
+
+/// Parse the `version` requirement of a package
+pub(crate) fn parse_version_req(
+    package: &str,
+    req: Option<&str>,
+) -> Result<Option<semver::VersionReq>, Error> {
+    req.map(|req| {
+        semver::VersionReq::parse(req)
+            .map_err(|e| Error::InvalidVersionReq(package.into(), req.into(), e.to_string()))
+    })
+    .transpose()
+}
+
+/// Parse a version that is close to semver, such as `v1.2.3` or `24.8`, as
+/// published by ecosystems that don't require semver
+pub(crate) fn parse_loose_version(version: &str) -> Option<semver::Version> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    if let Ok(version) = semver::Version::parse(version) {
+        return Some(version);
+    }
+    let parts = version
+        .split('.')
+        .map(|v| v.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    match parts[..] {
+        [major] => Some(semver::Version::new(major, 0, 0)),
+        [major, minor] => Some(semver::Version::new(major, minor, 0)),
+        _ => None,
+    }
+}
+
+/// The newest versions of a package that can be installed
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatestVersion {
+    /// The newest version matching the version requirement of the package
+    pub allowed: PackageVersion,
+    /// The newest version regardless of the version requirement
+    pub available: PackageVersion,
+}
+
+impl LatestVersion {
+    /// Used when the package has no version requirement
+    pub fn unconstrained(version: PackageVersion) -> Self {
+        Self {
+            allowed: version.clone(),
+            available: version,
+        }
+    }
+
+    /// Select the newest versions out of all the published `versions`,
+    /// pre-releases are only allowed if the requirement asks for them
+    pub fn select(
+        req: Option<&semver::VersionReq>,
+        versions: impl IntoIterator<Item = semver::Version>,
+    ) -> Self {
+        let mut allowed = None;
+        let mut available = None;
+
+        for version in versions {
+            // NOTE: Without a requirement only releases are allowed, as with `*`
+            if req.map_or(version.pre.is_empty(), |req| req.matches(&version))
+                && allowed.as_ref().is_none_or(|cur| *cur < version)
+            {
+                allowed = Some(version.clone());
+            }
+            if version.pre.is_empty() && available.as_ref().is_none_or(|cur| *cur < version) {
+                available = Some(version);
+            }
+        }
+
+        let into_version =
+            |v: Option<semver::Version>| v.map_or(PackageVersion::None, PackageVersion::Semver);
+
+        Self {
+            allowed: into_version(allowed),
+            available: into_version(available),
+        }
+    }
+}
+
+/// Something about an installed package that doesn't match the config
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PackageWarning {
+    /// The package was installed with other features than the ones declared in the config
+    FeaturesDiffer {
+        installed: CrateFeatures,
+        declared: CrateFeatures,
+    },
+}
+
+impl fmt::Display for PackageWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FeaturesDiffer {
+                installed,
+                declared,
+            } => write!(
+                f,
+                "installed with features {installed}, but {declared} is declared"
+            ),
+        }
+    }
+}
+
+/// A commit between the installed and the target version of a package
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangedCommit {
+    pub id: String,
+    /// The first line of the commit message
+    pub summary: String,
+    pub author: String,
+    pub date: Option<jiff::Timestamp>,
+}
+
+/// A release between the installed and the target version of a package
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChangedRelease {
+    pub version: semver::Version,
+    pub tag: String,
+    /// The release notes, usually markdown
+    pub notes: String,
+}
+
+/// What changed between two versions of a package
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PackageChanges {
+    /// The commits that are new in the target version, oldest first
+    Commits(Vec<ChangedCommit>),
+    /// The releases newer than the installed version up to the target version, newest first
+    Releases(Vec<ChangedRelease>),
+}
+
+/// Describes where a package comes from and where it is installed
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackageDetails {
+    /// Where the package is installed from, such as `crates.io` or a repository
+    pub source: Option<String>,
+    /// The directory the package is installed into, if it is known
+    pub location: Option<PathBuf>,
+    /// The binaries installed by the package, empty if they are unknown
+    pub bins: Vec<PathBuf>,
+    /// The environment variables set when installing the package
+    pub environment: Vec<(String, String)>,
+}
+
+/// How the installed version of a package compares to the target version
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateStatus {
+    NotInstalled,
+    UpToDate,
+    /// The target version is newer than the installed version
+    Outdated,
+    /// The installed version is newer than the target version, such as when
+    /// the version requirement was lowered
+    AheadOfRemote,
+    /// The installed and the target version both have changes the other is missing
+    Diverged,
+    /// The versions differ but can't be ordered, such as commits of a plain git remote
+    Changed,
+    /// The target version is unknown
+    Unknown,
+}
+
+impl UpdateStatus {
+    /// Calculate the status from how the `installed` version compares to the `target` version
+    pub fn new(installed: &PackageVersion, target: &PackageVersion, order: VersionOrder) -> Self {
+        match (installed, target) {
+            (PackageVersion::None, _) => Self::NotInstalled,
+            (_, PackageVersion::None) => Self::Unknown,
+            _ => match order {
+                VersionOrder::Same => Self::UpToDate,
+                VersionOrder::Older => Self::Outdated,
+                VersionOrder::Newer => Self::AheadOfRemote,
+                VersionOrder::Diverged => Self::Diverged,
+                // NOTE: The comparison may fail even if the ids match, such as when offline
+                VersionOrder::Unknown if installed.is_same(target) => Self::UpToDate,
+                VersionOrder::Unknown => Self::Changed,
+            },
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -24,19 +287,48 @@ pub struct PackageStatus {
     pub installed_version: PackageVersion,
     /// The version of the local sources
     pub downloaded_version: PackageVersion,
-    /// The version of the latest files on the internet
+    /// The newest version on the internet that is allowed by the config
     pub latest_version: PackageVersion,
+    /// The newest version on the internet, may be newer than `latest_version`
+    /// if the config has a version requirement
+    pub available_version: PackageVersion,
+    /// The version recorded in `logix.lock`, or [PackageVersion::None] if it is not locked
+    /// or the lock is of another kind than the resolved versions
+    pub locked_version: PackageVersion,
+    /// How the installed version compares to the target version
+    pub update_status: UpdateStatus,
+    /// Differences between the installed package and the config
+    pub warnings: Vec<PackageWarning>,
 }
 
 impl PackageStatus {
+    /// The version that should be installed, the locked version takes precedence over the latest
+    pub fn target_version(&self) -> &PackageVersion {
+        match self.locked_version {
+            PackageVersion::None => &self.latest_version,
+            PackageVersion::Commit { .. }
+            | PackageVersion::Semver(_)
+            | PackageVersion::Other(_) => &self.locked_version,
+        }
+    }
+
+    /// Check if there are downloaded sources that has not been built and installed
+    pub fn is_downloaded_not_built(&self) -> bool {
+        self.downloaded_version != PackageVersion::None
+            && !self.downloaded_version.is_same(&self.installed_version)
+    }
+
+    /// Check if the target version should be installed. A package ahead of
+    /// the remote is only downgraded to match `logix.lock`, while diverged
+    /// and changed packages follow the remote as that is what the config tracks
     pub fn need_update(&self) -> bool {
-        if self.latest_version != self.installed_version {
-            match self.latest_version {
-                PackageVersion::None => false,
-                PackageVersion::Commit { .. } | PackageVersion::Semver(_) => true,
-            }
-        } else {
-            false
+        match self.update_status {
+            UpdateStatus::NotInstalled => *self.target_version() != PackageVersion::None,
+            UpdateStatus::Unknown => false,
+            // NOTE: Reinstalling will fix the warnings
+            UpdateStatus::UpToDate => !self.warnings.is_empty(),
+            UpdateStatus::Outdated | UpdateStatus::Diverged | UpdateStatus::Changed => true,
+            UpdateStatus::AheadOfRemote => self.locked_version != PackageVersion::None,
         }
     }
 }
@@ -45,76 +337,174 @@ impl PackageStatus {
 pub struct ManagedPackage<'a> {
     name: Arc<str>,
     package: &'a Package,
+    defaults: &'a PackageDefaults,
+    locked: Option<&'a PackageVersion>,
 }
 
 impl<'a> ManagedPackage<'a> {
-    pub fn new(name: &Arc<str>, package: &'a Package) -> Self {
+    pub fn new(
+        name: &Arc<str>,
+        package: &'a Package,
+        defaults: &'a PackageDefaults,
+        locked: Option<&'a PackageVersion>,
+    ) -> Self {
         Self {
             name: name.clone(),
             package,
+            defaults,
+            locked,
         }
     }
 
+    fn locked_version(&self) -> PackageVersion {
+        self.locked.cloned().unwrap_or(PackageVersion::None)
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// The config of the package
+    pub fn package(&self) -> &'a Package {
+        self.package
+    }
+
+    pub fn defaults(&self) -> &'a PackageDefaults {
+        self.defaults
+    }
+
+    fn provider<'s>(&self, state: &'s SystemState) -> Result<&'s dyn PackageProvider, Error> {
+        state
+            .package_provider(self.package)
+            .ok_or_else(|| Error::NoPackageProvider(format!("the package {:?}", self.name)))
+    }
+
+    /// Calculate the status of the packages in parallel, the result is in the same order as `packages`
+    pub fn calculate_status_of_all(
+        state: &SystemState,
+        packages: &[Self],
+    ) -> Vec<Result<PackageStatus, Error>> {
+        packages
+            .par_iter()
+            .map(|package| package.calculate_status(state))
+            .collect()
+    }
+
     pub fn calculate_status(&self, state: &SystemState) -> Result<PackageStatus, Error> {
-        match self.package {
-            Package::RustCrate {
-                crate_name,
-                source,
-                config_dir: _,
-                environment: _,
-            } => {
-                let crate_spec =
-                    CrateSpec::new(crate_name.as_deref().unwrap_or(&self.name), source.as_ref());
-
-                Ok(PackageStatus {
-                    installed_version: state.cargo_package_version(&crate_spec)?,
-                    downloaded_version: PackageVersion::None,
-                    latest_version: state.cargo_latest_package_version(&crate_spec)?,
-                })
-            }
-            Package::Custom {
-                source,
-                local_dir: _,  // TODO: Use this for downloaded_version
-                config_dir: _, // TODO: Use this for downloaded_version
-            } => Ok(PackageStatus {
-                installed_version: PackageVersion::None,
-                downloaded_version: PackageVersion::None,
-                latest_version: match source {
-                    crate::config::Source::GitHub { owner, repo } => {
-                        let gh = GitHubRepo::new(owner, repo, state.cache());
-                        let info = gh.get_info()?;
-                        let branch = gh.get_branch_info(&info.default_branch)?;
-                        PackageVersion::Commit {
-                            id: branch.commit.sha,
-                            date: branch.commit.commit.author.date,
-                        }
-                    }
-                },
-            }),
+        let provider = self.provider(state)?;
+        let ctx = state.context();
+        let latest = provider.latest_version(ctx, self)?;
+        let mut locked_version = self.locked_version();
+        if locked_version != PackageVersion::None
+            && latest.allowed != PackageVersion::None
+            && !locked_version.is_same_kind(&latest.allowed)
+        {
+            // NOTE: The lock is from before the source changed, installing it would never converge
+            log::warn!(
+                "Ignoring the locked version {locked_version} of package {:?} as {} is resolved",
+                self.name,
+                latest.allowed
+            );
+            locked_version = PackageVersion::None;
         }
+        let mut status = PackageStatus {
+            installed_version: provider.installed_version(ctx, self)?,
+            downloaded_version: provider.downloaded_version(ctx, self)?,
+            latest_version: latest.allowed,
+            available_version: latest.available,
+            locked_version,
+            update_status: UpdateStatus::Unknown,
+            warnings: provider.warnings(ctx, self)?,
+        };
+
+        let installed = &status.installed_version;
+        let target = status.target_version();
+        let order = match (installed, target) {
+            (PackageVersion::None, _) | (_, PackageVersion::None) => installed.compare(target),
+            _ => provider
+                .compare_versions(ctx, self, installed, target)
+                .unwrap_or_else(|e| {
+                    log::warn!(
+                        "Failed to compare the versions of package {:?}: {e}",
+                        self.name
+                    );
+                    VersionOrder::Unknown
+                }),
+        };
+        status.update_status = UpdateStatus::new(installed, target, order);
+
+        Ok(status)
     }
 
-    pub fn install_update(&self, state: &mut SystemState) -> Result<PackageVersion, Error> {
-        match self.package {
-            Package::RustCrate {
-                crate_name,
-                source,
-                config_dir: _,
-                environment: _,
-            } => {
-                let crate_spec =
-                    CrateSpec::new(crate_name.as_deref().unwrap_or(&self.name), source.as_ref());
-                state.cargo_install_package(&crate_spec)
-            }
-            Package::Custom { .. } => todo!(),
-        }
+    /// Install the specified `version` of the package, usually the target version of the status
+    pub fn install_update(
+        &self,
+        state: &mut SystemState,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        let (provider, ctx) = state
+            .package_provider_mut(self.package)
+            .ok_or_else(|| Error::NoPackageProvider(format!("the package {:?}", self.name)))?;
+        let installed_version = provider.install(ctx, self, version)?;
+
+        self.record_installed(state, &installed_version)?;
+
+        Ok(installed_version)
+    }
+
+    /// Describes what changed between the installed and the target version of
+    /// the package, `None` if it is not known for the package
+    pub fn changes(
+        &self,
+        state: &SystemState,
+        status: &PackageStatus,
+    ) -> Result<Option<PackageChanges>, Error> {
+        self.provider(state)?.changes(
+            state.context(),
+            self,
+            &status.installed_version,
+            status.target_version(),
+        )
+    }
+
+    /// Describes where the package comes from and where it is installed
+    pub fn details(&self, state: &SystemState) -> Result<PackageDetails, Error> {
+        self.provider(state)?.details(state.context(), self)
+    }
+
+    /// Describes how the package is installed, used to detect packages removed from the config
+    pub fn installed_kind(&self, state: &SystemState) -> Result<InstalledKind, Error> {
+        self.provider(state)?.installed_kind(state.context(), self)
     }
 
-    pub fn is_custom(&self) -> bool {
-        matches!(self.package, Package::Custom { .. })
+    /// Check if logix only reports that the package needs to be installed,
+    /// leaving the installation to the user
+    pub fn is_check_only(&self, state: &SystemState) -> bool {
+        self.provider(state)
+            .is_ok_and(|provider| provider.is_check_only(self))
+    }
+
+    /// Record that the package is installed so it can be uninstalled once
+    /// it is removed from the config
+    pub fn record_installed(
+        &self,
+        state: &mut SystemState,
+        version: &PackageVersion,
+    ) -> Result<(), Error> {
+        if self.is_check_only(state) {
+            // NOTE: The package wasn't installed by logix, so it is not tracked
+            return Ok(());
+        }
+
+        match version {
+            PackageVersion::None => Ok(()),
+            PackageVersion::Commit { .. }
+            | PackageVersion::Semver(_)
+            | PackageVersion::Other(_) => state.record_installed(InstalledPackage {
+                name: self.name.to_string(),
+                kind: self.installed_kind(state)?,
+                version: version.clone(),
+            }),
+        }
     }
 }
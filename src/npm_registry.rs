@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use jiff::ToSpan;
+use serde::{de::IgnoredAny, Deserialize};
+
+use crate::{cache::Cache, error::Error, url_fetch::UrlFetch};
+
+#[derive(Deserialize)]
+struct NpmPackument {
+    versions: BTreeMap<String, IgnoredAny>,
+}
+
+pub struct NpmPackage {
+    url: String,
+    key: String,
+    cache: Cache,
+}
+
+impl NpmPackage {
+    pub fn new(name: &str, cache: &Cache) -> Self {
+        Self {
+            // NOTE: The slash of scoped packages such as `@scope/name` must be escaped
+            url: format!("https://registry.npmjs.org/{}", name.replace('/', "%2F")),
+            key: format!("npm/{name}/versions"),
+            cache: cache.clone(),
+        }
+    }
+
+    /// Get all the published versions of the package
+    pub fn get_versions(&self) -> Result<Vec<semver::Version>, Error> {
+        self.cache.get_or_insert(&self.key, 1.hour(), || {
+            let packument: NpmPackument = UrlFetch::new(&self.url)?.get()?.json()?;
+            Ok(packument
+                .versions
+                .into_keys()
+                .filter_map(|v| semver::Version::parse(&v).ok())
+                .collect())
+        })
+    }
+}
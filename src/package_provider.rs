@@ -0,0 +1,275 @@
+use std::{any::Any, path::PathBuf};
+
+use jiff::Span;
+use logix_type::types::ValidPath;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    based_path::BasedPath,
+    cache::Cache,
+    config::Package,
+    error::Error,
+    installed_packages::{InstalledKind, InstalledPackage, InstalledPackages},
+    managed_package::{
+        LatestVersion, ManagedPackage, PackageChanges, PackageDetails, PackageVersion,
+        PackageWarning, VersionOrder,
+    },
+};
+
+pub use crate::helpers::{
+    cargo::CargoState, custom::CustomPackages, github_release::GitHubReleases, go::GoState,
+    npm::NpmState, plugin::PluginPackages, python::PythonState, system::SystemPackages,
+    url_archive::ArchivePackages,
+};
+
+/// The state shared by all the package providers
+pub struct ProviderContext {
+    cache: Cache,
+    installed: InstalledPackages,
+    home_dir: BasedPath,
+    packages_dir: BasedPath,
+    archives_dir: BasedPath,
+    user_bin: BasedPath,
+}
+
+impl ProviderContext {
+    pub(crate) fn new(
+        cache: Cache,
+        installed: InstalledPackages,
+        home_dir: BasedPath,
+        cache_dir: &BasedPath,
+        user_bin: BasedPath,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            cache,
+            installed,
+            home_dir,
+            packages_dir: cache_dir.join("packages")?,
+            archives_dir: cache_dir.join("archives")?,
+            user_bin,
+        })
+    }
+
+    pub(crate) fn installed(&self) -> &InstalledPackages {
+        &self.installed
+    }
+
+    pub(crate) fn installed_mut(&mut self) -> &mut InstalledPackages {
+        &mut self.installed
+    }
+
+    /// Returns the record of the package if it was installed by logix
+    pub fn installed_package(&self, name: &str, kind: &InstalledKind) -> Option<&InstalledPackage> {
+        self.installed
+            .iter()
+            .find(|v| v.name == name && v.kind == *kind)
+    }
+
+    /// The directory holding the sources of a custom package, relative
+    /// paths are relative to the home directory
+    pub fn custom_package_dir(&self, name: &str, local_dir: Option<&ValidPath>) -> PathBuf {
+        match local_dir {
+            Some(ValidPath::Full(path)) => path.to_path_buf(),
+            Some(path) => self.home_dir.as_path().join(path),
+            None => self.packages_dir.as_path().join(name),
+        }
+    }
+
+    /// The bin directory of the user, such as `~/.local/bin`
+    pub fn user_bin(&self) -> &BasedPath {
+        &self.user_bin
+    }
+
+    /// The path of a binary installed into the bin directory of the user
+    pub fn user_bin_path(&self, bin: &str) -> PathBuf {
+        self.user_bin.as_path().join(bin)
+    }
+
+    /// The directory holding the extracted versions of an archive package
+    pub fn archive_package_dir(&self, name: &str) -> PathBuf {
+        self.archives_dir.as_path().join(name)
+    }
+
+    pub fn cached<T: Serialize + DeserializeOwned>(
+        &self,
+        key: &str,
+        ttl: Span,
+        f: impl FnOnce() -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self.cache.get_or_insert(key, ttl, f)
+    }
+
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+}
+
+/// Installs and keeps track of one kind of package, such as the crates
+/// installed using cargo. Providers are registered in the
+/// [SystemState](crate::system_state::SystemState), which picks the provider
+/// of a package using [PackageProvider::handles]
+pub trait PackageProvider: Any + Send + Sync {
+    /// Check if the provider installs the package
+    fn handles(&self, package: &Package) -> bool;
+
+    /// Check if the provider is responsible for packages installed as `kind`
+    fn handles_installed(&self, kind: &InstalledKind) -> bool;
+
+    /// Returns the installed version, [PackageVersion::None] if it isn't installed
+    fn installed_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error>;
+
+    /// Returns the newest versions that can be installed
+    fn latest_version(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error>;
+
+    /// Returns the version of the local sources, if the package is built from source
+    fn downloaded_version(
+        &self,
+        _ctx: &ProviderContext,
+        _package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        Ok(PackageVersion::None)
+    }
+
+    /// Returns the differences between the installed package and the config
+    fn warnings(
+        &self,
+        _ctx: &ProviderContext,
+        _package: &ManagedPackage,
+    ) -> Result<Vec<PackageWarning>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Compare two versions of the package, by default using [PackageVersion::compare]
+    fn compare_versions(
+        &self,
+        _ctx: &ProviderContext,
+        _package: &ManagedPackage,
+        a: &PackageVersion,
+        b: &PackageVersion,
+    ) -> Result<VersionOrder, Error> {
+        Ok(a.compare(b))
+    }
+
+    /// Returns what changed between two versions of the package, `None` if it is unknown
+    fn changes(
+        &self,
+        _ctx: &ProviderContext,
+        _package: &ManagedPackage,
+        _from: &PackageVersion,
+        _to: &PackageVersion,
+    ) -> Result<Option<PackageChanges>, Error> {
+        Ok(None)
+    }
+
+    /// Describes where the package comes from and where it is installed
+    fn details(
+        &self,
+        _ctx: &ProviderContext,
+        _package: &ManagedPackage,
+    ) -> Result<PackageDetails, Error> {
+        Ok(PackageDetails::default())
+    }
+
+    /// Install the specified `version` of the package, returns the version that was installed
+    fn install(
+        &mut self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error>;
+
+    /// Describes how the package is installed, used to detect packages removed from the config
+    fn installed_kind(
+        &self,
+        ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error>;
+
+    /// Check if logix only reports that the package needs to be installed,
+    /// leaving the installation to the user
+    fn is_check_only(&self, _package: &ManagedPackage) -> bool {
+        false
+    }
+
+    /// Check if the package is still installed on the system
+    fn is_installed(&self, ctx: &ProviderContext, kind: &InstalledKind) -> bool;
+
+    /// Uninstall the package, it is only called for packages that are installed
+    fn uninstall(&mut self, ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error>;
+
+    /// Returns the installed packages, by default the packages recorded as
+    /// installed by logix that are still installed
+    fn list_installed(&self, ctx: &ProviderContext) -> Result<Vec<InstalledPackage>, Error> {
+        Ok(ctx
+            .installed
+            .iter()
+            .filter(|v| self.handles_installed(&v.kind) && self.is_installed(ctx, &v.kind))
+            .cloned()
+            .collect())
+    }
+
+    /// Reload any state that may have been changed by installing packages
+    fn refresh(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// The registered package providers, providers registered later take precedence
+#[derive(Default)]
+pub(crate) struct ProviderRegistry {
+    providers: Vec<Box<dyn PackageProvider>>,
+}
+
+impl ProviderRegistry {
+    pub fn register(&mut self, provider: Box<dyn PackageProvider>) {
+        self.providers.push(provider);
+    }
+
+    fn position(&self, f: impl Fn(&dyn PackageProvider) -> bool) -> Option<usize> {
+        self.providers.iter().rposition(|v| f(v.as_ref()))
+    }
+
+    pub fn get(&self, package: &Package) -> Option<&dyn PackageProvider> {
+        self.position(|v| v.handles(package))
+            .map(|i| self.providers[i].as_ref())
+    }
+
+    pub fn get_mut(&mut self, package: &Package) -> Option<&mut dyn PackageProvider> {
+        self.position(|v| v.handles(package))
+            .map(|i| self.providers[i].as_mut())
+    }
+
+    pub fn get_installed(&self, kind: &InstalledKind) -> Option<&dyn PackageProvider> {
+        self.position(|v| v.handles_installed(kind))
+            .map(|i| self.providers[i].as_ref())
+    }
+
+    pub fn get_installed_mut(&mut self, kind: &InstalledKind) -> Option<&mut dyn PackageProvider> {
+        self.position(|v| v.handles_installed(kind))
+            .map(|i| self.providers[i].as_mut())
+    }
+
+    /// Find a provider by its type
+    pub fn find<P: PackageProvider>(&self) -> Option<&P> {
+        self.providers
+            .iter()
+            .rev()
+            .find_map(|v| (v.as_ref() as &dyn Any).downcast_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn PackageProvider> {
+        self.providers.iter().map(|v| v.as_ref())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn PackageProvider>> {
+        self.providers.iter_mut()
+    }
+}
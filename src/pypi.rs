@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use jiff::ToSpan;
+use serde::Deserialize;
+
+use crate::{
+    cache::Cache, error::Error, managed_package::parse_loose_version, url_fetch::UrlFetch,
+};
+
+#[derive(Deserialize)]
+struct PyPiProject {
+    releases: BTreeMap<String, Vec<PyPiFile>>,
+}
+
+#[derive(Deserialize)]
+struct PyPiFile {
+    #[serde(default)]
+    yanked: bool,
+}
+
+pub struct PyPiPackage {
+    url: String,
+    key: String,
+    cache: Cache,
+}
+
+impl PyPiPackage {
+    pub fn new(name: &str, cache: &Cache) -> Self {
+        Self {
+            url: format!("https://pypi.org/pypi/{name}/json"),
+            key: format!("pypi/{name}/versions"),
+            cache: cache.clone(),
+        }
+    }
+
+    /// Get the released versions of the package that are not yanked, versions
+    /// that can't be parsed as semver such as `1.0rc1` are skipped
+    pub fn get_versions(&self) -> Result<Vec<semver::Version>, Error> {
+        let versions: Vec<String> = self.cache.get_or_insert(&self.key, 1.hour(), || {
+            let project: PyPiProject = UrlFetch::new(&self.url)?.get()?.json()?;
+            Ok(project
+                .releases
+                .into_iter()
+                .filter(|(_, files)| files.iter().any(|v| !v.yanked))
+                .map(|(version, _)| version)
+                .collect())
+        })?;
+
+        Ok(versions
+            .iter()
+            .filter_map(|v| parse_loose_version(v))
+            .collect())
+    }
+}
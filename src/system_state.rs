@@ -1,59 +1,140 @@
-use jiff::{Span, ToSpan};
-use serde::{de::DeserializeOwned, Serialize};
-
 use crate::{
     cache::Cache,
+    config::Package,
     env::Env,
     error::Error,
-    helpers::cargo::{CargoState, CrateSpec},
-    managed_package::PackageVersion,
+    installed_packages::{InstalledKind, InstalledPackage, InstalledPackages},
+    package_provider::{
+        ArchivePackages, CargoState, CustomPackages, GitHubReleases, GoState, NpmState,
+        PackageProvider, PluginPackages, ProviderContext, ProviderRegistry, PythonState,
+        SystemPackages,
+    },
+};
+
+pub use crate::helpers::{
+    cargo::{
+        CrateBuild, CrateFeatures, CrateSource, CrateSpec, GitReference, GitSource, InstalledCrate,
+    },
+    system::SystemPackageManager,
 };
 
 pub struct SystemState {
-    cargo: CargoState,
-    cache: Cache,
+    providers: ProviderRegistry,
+    ctx: ProviderContext,
 }
 
 impl SystemState {
     pub fn init(env: &Env) -> Result<Self, Error> {
+        let cache_dir = env.user_cache().join("logix")?;
+
+        let mut providers = ProviderRegistry::default();
+        providers.register(Box::new(CargoState::init(env.cargo_home())?));
+        providers.register(Box::new(CustomPackages));
+        providers.register(Box::new(GitHubReleases));
+        providers.register(Box::new(ArchivePackages));
+        providers.register(Box::new(PythonState::new()));
+        providers.register(Box::new(NpmState::new(
+            &env.dotfiles().local_path().join(".local")?,
+        )));
+        providers.register(Box::new(GoState::new(env.go_bin())));
+        providers.register(Box::new(SystemPackages::init(env.os_release())));
+        providers.register(Box::new(PluginPackages));
+
         Ok(Self {
-            cargo: CargoState::init()?,
-            cache: Cache::new(env.user_cache().join("logix")?),
+            providers,
+            ctx: ProviderContext::new(
+                Cache::new(cache_dir.clone()),
+                InstalledPackages::load(env.user_state().join("logix/installed.json")?)?,
+                env.dotfiles().local_path().clone(),
+                &cache_dir,
+                env.user_bin().clone(),
+            )?,
         })
     }
 
+    /// Add or replace how a kind of package is installed. The provider takes
+    /// precedence over the ones registered before it, including the built-in ones.
+    /// New kinds of packages are declared as [Package::Extension], which no
+    /// built-in provider handles
+    pub fn register_provider(&mut self, provider: impl PackageProvider) {
+        self.providers.register(Box::new(provider));
+    }
+
+    /// Returns the registered provider of type `P`
+    pub fn provider<P: PackageProvider>(&self) -> Option<&P> {
+        self.providers.find()
+    }
+
+    /// Returns the provider that installs the package
+    pub fn package_provider(&self, package: &Package) -> Option<&dyn PackageProvider> {
+        self.providers.get(package)
+    }
+
+    pub(crate) fn package_provider_mut(
+        &mut self,
+        package: &Package,
+    ) -> Option<(&mut dyn PackageProvider, &ProviderContext)> {
+        let provider = self.providers.get_mut(package)?;
+        Some((provider, &self.ctx))
+    }
+
+    /// The state shared by the providers
+    pub fn context(&self) -> &ProviderContext {
+        &self.ctx
+    }
+
     pub fn refresh_state(&mut self) -> Result<(), Error> {
-        let Self { cargo, cache: _ } = self;
-        cargo.refresh_state()?;
+        for provider in self.providers.iter_mut() {
+            provider.refresh()?;
+        }
         Ok(())
     }
 
-    pub fn cargo_package_version(&self, spec: &CrateSpec) -> Result<PackageVersion, Error> {
-        Ok(self.cargo.package_version(spec))
+    /// Returns the packages that has been installed by logix
+    pub fn installed_packages(&self) -> impl Iterator<Item = &InstalledPackage> {
+        self.ctx.installed().iter()
+    }
+
+    /// Returns the record of the package if it was installed by logix
+    pub fn installed_package(&self, name: &str, kind: &InstalledKind) -> Option<&InstalledPackage> {
+        self.ctx.installed_package(name, kind)
+    }
+
+    /// Returns the packages installed on the system according to all the providers
+    pub fn list_installed(&self) -> Result<Vec<InstalledPackage>, Error> {
+        let mut ret = Vec::new();
+        for provider in self.providers.iter() {
+            ret.extend(provider.list_installed(&self.ctx)?);
+        }
+        Ok(ret)
     }
 
-    pub fn cargo_latest_package_version(&self, spec: &CrateSpec) -> Result<PackageVersion, Error> {
-        self.cached(
-            &format!("cargo/{}.latest", spec.cache_name()),
-            1.hour(),
-            || self.cargo.latest_package_version(spec, self.cache()),
-        )
+    /// Record that logix installed the package
+    pub fn record_installed(&mut self, package: InstalledPackage) -> Result<(), Error> {
+        self.ctx.installed_mut().insert(package)
     }
 
-    pub fn cargo_install_package(&mut self, spec: &CrateSpec) -> Result<PackageVersion, Error> {
-        self.cargo.install_package(spec)
+    /// Check if the package is still installed on the system
+    pub fn is_installed(&self, kind: &InstalledKind) -> bool {
+        self.providers
+            .get_installed(kind)
+            .is_some_and(|provider| provider.is_installed(&self.ctx, kind))
     }
 
-    pub fn cached<T: Serialize + DeserializeOwned>(
-        &self,
-        key: &str,
-        ttl: Span,
-        f: impl FnOnce() -> Result<T, Error>,
-    ) -> Result<T, Error> {
-        self.cache.get_or_insert(key, ttl, f)
+    /// Uninstall the package if it is still installed and forget that it was installed by logix
+    pub fn uninstall(&mut self, kind: &InstalledKind) -> Result<(), Error> {
+        let provider = self
+            .providers
+            .get_installed_mut(kind)
+            .ok_or_else(|| Error::NoPackageProvider(format!("the installed package {kind:?}")))?;
+        if provider.is_installed(&self.ctx, kind) {
+            provider.uninstall(&self.ctx, kind)?;
+        }
+        self.ctx.installed_mut().remove(kind)
     }
 
-    pub fn cache(&self) -> &Cache {
-        &self.cache
+    /// The package manager of the distribution, `None` if it is not supported
+    pub fn system_package_manager(&self) -> Option<SystemPackageManager> {
+        self.provider::<SystemPackages>()?.manager()
     }
 }
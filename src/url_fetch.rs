@@ -12,6 +12,14 @@ fn useragent() -> String {
     )
 }
 
+/// Encode a path, such as a branch name, so it can be used as a single component of the url
+pub fn encode_path(path: &str) -> String {
+    path.replace('%', "%25")
+        .replace('/', "%2F")
+        .replace('#', "%23")
+        .replace('?', "%3F")
+}
+
 pub struct UrlFetch {
     url: String,
     easy: curl::easy::Easy,
@@ -28,6 +36,12 @@ impl UrlFetch {
         })
     }
 
+    /// Follow redirects, needed when downloading files such as release assets
+    pub fn follow_location(mut self) -> Result<Self, Error> {
+        self.easy.follow_location(true)?;
+        Ok(self)
+    }
+
     pub fn get(mut self) -> Result<Response, Error> {
         let mut data = Vec::new();
         self.easy.get(true)?;
@@ -41,7 +55,8 @@ impl UrlFetch {
         }
         let status = self.easy.response_code()?;
 
-        if !(200..=299).contains(&status) {
+        // NOTE: Only HTTP has a status, it is zero for local `file://` urls
+        if status != 0 && !(200..=299).contains(&status) {
             return Err(Error::HttpRequest(
                 self.url.clone(),
                 format!("Server returned status {status}"),
@@ -65,6 +80,10 @@ impl Response {
         serde_json::from_slice(&self.data)
             .map_err(|e| Error::HttpRequestJson(self.url.clone(), e.to_string()))
     }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
 }
 
 impl From<curl::Error> for Error {
@@ -6,6 +6,20 @@ pub enum WalkEntry<'a> {
     Local(&'a Path),
     Both(&'a Path),
     Logix(&'a Path),
+    /// Failed to walk the local path, the remaining paths will still be visited
+    LocalError(&'a Path, std::io::ErrorKind),
+    /// Failed to walk the logix path, the remaining paths will still be visited
+    LogixError(&'a Path, std::io::ErrorKind),
+}
+
+/// Returns the path relative to `base` and the kind of the error
+fn walk_error<'a>(base: &Path, e: &'a walkdir::Error) -> (&'a Path, std::io::ErrorKind) {
+    (
+        e.path()
+            .and_then(|path| path.strip_prefix(base).ok())
+            .unwrap_or(Path::new("")),
+        e.io_error().map_or(std::io::ErrorKind::Other, |e| e.kind()),
+    )
 }
 
 pub fn walk_dirs(
@@ -24,8 +38,8 @@ pub fn walk_dirs(
             .flatten()
             .filter(|v| {
                 if let Ok(v) = v {
-                    // Ignore directories
-                    !v.path().is_dir()
+                    // Ignore directories, but not symlinks to directories as they are tracked as links
+                    !v.file_type().is_dir()
                 } else {
                     true
                 }
@@ -35,19 +49,40 @@ pub fn walk_dirs(
     let mut local_it = walk_iter(base.local_path());
     let mut logix_it = walk_iter(base.logix_path());
 
-    let mut next_local = || local_it.next().transpose().map_err(Error::WalkDir);
-    let mut next_logix = || logix_it.next().transpose().map_err(Error::WalkDir);
-
-    let mut cur_local = next_local()?;
-    let mut cur_logix = next_logix()?;
+    let mut cur_local = local_it.next();
+    let mut cur_logix = logix_it.next();
 
     loop {
+        if let Some(Err(e)) = &cur_local {
+            let (local_path, kind) = walk_error(base.local_path(), e);
+            log::warn!(
+                "Failed to walk {:?}: {e}",
+                base.local_path().join(local_path)
+            );
+            if local_filter.should_include(local_path) {
+                on_file(WalkEntry::LocalError(local_path, kind))?;
+            }
+            cur_local = local_it.next();
+            continue;
+        }
+
+        if let Some(Err(e)) = &cur_logix {
+            let (logix_path, kind) = walk_error(base.logix_path(), e);
+            log::warn!(
+                "Failed to walk {:?}: {e}",
+                base.logix_path().join(logix_path)
+            );
+            on_file(WalkEntry::LogixError(logix_path, kind))?;
+            cur_logix = logix_it.next();
+            continue;
+        }
+
         match (cur_local, cur_logix) {
-            (Some(local), Some(logix)) => {
+            (Some(Ok(local)), Some(Ok(logix))) => {
                 let local_path = local.path().strip_prefix(base.local_path()).unwrap();
                 if !local_filter.should_include(local_path) {
-                    cur_local = next_local()?;
-                    cur_logix = Some(logix);
+                    cur_local = local_it.next();
+                    cur_logix = Some(Ok(logix));
                     continue;
                 }
 
@@ -56,42 +91,43 @@ pub fn walk_dirs(
                 match local_path.cmp(logix_path) {
                     std::cmp::Ordering::Less => {
                         on_file(WalkEntry::Local(local_path))?;
-                        cur_local = next_local()?;
-                        cur_logix = Some(logix);
+                        cur_local = local_it.next();
+                        cur_logix = Some(Ok(logix));
                     }
                     std::cmp::Ordering::Equal => {
                         on_file(WalkEntry::Both(local_path))?;
-                        cur_local = next_local()?;
-                        cur_logix = next_logix()?;
+                        cur_local = local_it.next();
+                        cur_logix = logix_it.next();
                     }
                     std::cmp::Ordering::Greater => {
                         on_file(WalkEntry::Logix(logix_path))?;
-                        cur_local = Some(local);
-                        cur_logix = next_logix()?;
+                        cur_local = Some(Ok(local));
+                        cur_logix = logix_it.next();
                     }
                 }
             }
-            (Some(local), None) => {
+            (Some(Ok(local)), None) => {
                 let local_path = local.path().strip_prefix(base.local_path()).unwrap();
                 if !local_filter.should_include(local_path) {
-                    cur_local = next_local()?;
+                    cur_local = local_it.next();
                     cur_logix = None;
                     continue;
                 }
 
                 on_file(WalkEntry::Local(local_path))?;
 
-                cur_local = next_local()?;
+                cur_local = local_it.next();
                 cur_logix = None;
             }
-            (None, Some(logix)) => {
+            (None, Some(Ok(logix))) => {
                 let logix_path = logix.path().strip_prefix(base.logix_path()).unwrap();
                 on_file(WalkEntry::Logix(logix_path))?;
 
                 cur_local = None;
-                cur_logix = next_logix()?;
+                cur_logix = logix_it.next();
             }
             (None, None) => return Ok(()),
+            (Some(Err(_)), _) | (_, Some(Err(_))) => unreachable!("errors are handled above"),
         }
     }
 }
@@ -0,0 +1,269 @@
+#![cfg(unix)]
+
+use std::path::Path;
+
+use logix::{
+    error::Error, file_index::ContentHash, installed_packages::InstalledKind,
+    managed_package::PackageVersion, system_state::SystemState,
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      tool: Archive {
+        url_template: "https://example.com/tool-{version}.tar.gz"
+        version: "1.3.0"
+        sha256: "0000000000000000000000000000000000000000000000000000000000000000"
+        strip_components: 1
+        bins: ["bin/tool"]
+      }
+    }
+  }
+}
+"#;
+
+fn semver(v: &str) -> PackageVersion {
+    PackageVersion::Semver(v.parse().unwrap())
+}
+
+#[test]
+fn installed_archive() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+    let package = logix.find_package("tool").unwrap();
+
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, PackageVersion::None);
+    assert_eq!(
+        status.latest_version,
+        PackageVersion::Semver("1.3.0".parse().unwrap())
+    );
+    assert!(status.need_update());
+
+    // The installed version is the one the `current` symlink points to
+    let dir = fs.home_path(".cache/logix/archives/tool");
+    fs.write_home_file(".cache/logix/archives/tool/1.2.0/bin/tool", "#!/bin/sh\n");
+    std::os::unix::fs::symlink("1.2.0", dir.join("current").unwrap()).unwrap();
+    std::fs::create_dir_all(fs.home_path(".local/bin")).unwrap();
+    std::os::unix::fs::symlink(
+        dir.join("current/bin/tool").unwrap(),
+        fs.home_path(".local/bin/tool"),
+    )
+    .unwrap();
+    fs.write_home_file(".local/bin/other", "#!/bin/sh\n");
+
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(
+        status.installed_version,
+        PackageVersion::Semver("1.2.0".parse().unwrap())
+    );
+    assert!(status.need_update());
+
+    // Uninstalling removes the extracted versions and the links to them
+    let kind = package.installed_kind(&state).unwrap();
+    assert_eq!(
+        kind,
+        InstalledKind::Archive {
+            dir: dir.to_path_buf()
+        }
+    );
+    let mut state = state;
+    assert!(state.is_installed(&kind));
+    state.uninstall(&kind).unwrap();
+    assert!(!state.is_installed(&kind));
+    assert!(!dir.exists());
+    assert!(fs.home_path(".local/bin/tool").symlink_metadata().is_err());
+    assert!(fs.home_path(".local/bin/other").exists());
+}
+
+static INSTALL_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      tool: Archive {
+        url_template: "URL"
+        version: "VERSION"
+        sha256: "SHA256"
+        strip_components: 1
+        bins: ["bin/tool"]
+      }
+    }
+  }
+}
+"#;
+
+/// Publish the archive of `version` in `downloads` of the home directory, returns its checksum
+fn publish(fs: &helper::TestFs, version: &str) -> String {
+    let data = helper::tar_gz(&[
+        (
+            &format!("tool-{version}/bin/tool"),
+            &format!("#!/bin/sh\necho {version}\n"),
+        ),
+        (&format!("tool-{version}/share/README.md"), "# Tool\n"),
+    ]);
+    fs.write_home_bytes(&format!("downloads/tool-{version}.tar.gz"), &data);
+    ContentHash::of_bytes(&data).as_hex().into()
+}
+
+fn write_config(fs: &helper::TestFs, version: &str, sha256: &str) {
+    let url = helper::file_url(&fs.home_path("downloads/tool-{version}.tar.gz"));
+    fs.write_config_file(
+        "logix/root.logix",
+        &INSTALL_LOGIX
+            .replace("URL", &url)
+            .replace("VERSION", version)
+            .replace("SHA256", sha256),
+    );
+}
+
+fn install(fs: &helper::TestFs) -> Result<PackageVersion, Error> {
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    let package = logix.find_package("tool").unwrap();
+    let target = package.calculate_status(&state)?.latest_version;
+    package.install_update(&mut state, &target)
+}
+
+#[test]
+fn install_archive() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let first = publish(&fs, "1.2.0");
+    let second = publish(&fs, "1.3.0");
+    let dir = fs.home_path(".cache/logix/archives/tool");
+    let bin = fs.home_path(".local/bin/tool");
+
+    write_config(&fs, "1.2.0", &first);
+    assert_eq!(install(&fs).unwrap(), semver("1.2.0"));
+
+    // The leading directory is stripped and the binaries are linked from the current version
+    assert!(dir.join("1.2.0/bin/tool").unwrap().is_file());
+    assert!(dir.join("1.2.0/share/README.md").unwrap().is_file());
+    assert!(!dir.join("1.2.0/tool-1.2.0").unwrap().exists());
+    assert!(!dir.join("1.2.0.tmp").unwrap().exists());
+    assert_eq!(
+        std::fs::read_link(dir.join("current").unwrap()).unwrap(),
+        Path::new("1.2.0")
+    );
+    assert_eq!(
+        std::fs::read_link(&bin).unwrap(),
+        dir.join("current/bin/tool").unwrap().as_path()
+    );
+    assert_eq!(
+        std::fs::read_to_string(&bin).unwrap(),
+        "#!/bin/sh\necho 1.2.0\n"
+    );
+
+    // A download with another checksum is rejected before anything is extracted
+    write_config(&fs, "1.3.0", &first);
+    let err = install(&fs).unwrap_err();
+    assert!(matches!(err, Error::ChecksumMismatch(..)), "{err}");
+    assert!(!dir.join("1.3.0").unwrap().exists());
+    assert_eq!(
+        std::fs::read_link(dir.join("current").unwrap()).unwrap(),
+        Path::new("1.2.0")
+    );
+
+    // Updating switches the `current` symlink, keeping the previous version
+    write_config(&fs, "1.3.0", &second);
+    assert_eq!(install(&fs).unwrap(), semver("1.3.0"));
+    assert_eq!(
+        std::fs::read_link(dir.join("current").unwrap()).unwrap(),
+        Path::new("1.3.0")
+    );
+    assert!(!dir.join("current.logix-tmp").unwrap().exists());
+    assert!(dir.join("1.2.0/bin/tool").unwrap().is_file());
+    assert_eq!(
+        std::fs::read_to_string(&bin).unwrap(),
+        "#!/bin/sh\necho 1.3.0\n"
+    );
+}
+
+/// Publish an archive of `version` whose entries are links, given as `(path, target, hard)`,
+/// followed by the executable files given as `(path, content)`
+fn publish_links(
+    fs: &helper::TestFs,
+    version: &str,
+    links: &[(&str, &Path, bool)],
+    files: &[(&str, &str)],
+) -> String {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    for (path, target, hard) in links {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(if *hard {
+            tar::EntryType::Link
+        } else {
+            tar::EntryType::Symlink
+        });
+        header.set_size(0);
+        builder.append_link(&mut header, path, target).unwrap();
+    }
+    for (path, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, data.as_bytes())
+            .unwrap();
+    }
+    let data = builder.into_inner().unwrap().finish().unwrap();
+    fs.write_home_bytes(&format!("downloads/tool-{version}.tar.gz"), &data);
+    ContentHash::of_bytes(&data).as_hex().into()
+}
+
+#[test]
+fn malicious_archive() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let outside = fs.home_path("outside");
+    let secret = outside.join("secret").unwrap();
+    fs.write_home_file("outside/secret", "secret\n");
+    let dir = fs.home_path(".cache/logix/archives/tool");
+
+    // A file can't be written through a symlink from the archive
+    let sha256 = publish_links(
+        &fs,
+        "1.0.0",
+        &[("tool-1.0.0/escape", &outside, false)],
+        &[("tool-1.0.0/escape/pwned", "pwned\n")],
+    );
+    write_config(&fs, "1.0.0", &sha256);
+    let err = install(&fs).unwrap_err();
+    assert!(matches!(err, Error::ExtractArchive(..)), "{err}");
+    assert!(!outside.join("pwned").unwrap().exists());
+    assert!(!dir.join("1.0.0").unwrap().exists());
+
+    // Replacing the symlink with a file doesn't follow it either
+    let sha256 = publish_links(
+        &fs,
+        "1.1.0",
+        &[("tool-1.1.0/bin/tool", &secret, false)],
+        &[("tool-1.1.0/bin/tool", "#!/bin/sh\necho 1.1.0\n")],
+    );
+    write_config(&fs, "1.1.0", &sha256);
+    assert_eq!(install(&fs).unwrap(), semver("1.1.0"));
+    assert_eq!(std::fs::read_to_string(&secret).unwrap(), "secret\n");
+    assert_eq!(
+        std::fs::read_to_string(dir.join("1.1.0/bin/tool").unwrap()).unwrap(),
+        "#!/bin/sh\necho 1.1.0\n"
+    );
+
+    // Hard links can only target files of the archive
+    let sha256 = publish_links(&fs, "1.2.0", &[("tool-1.2.0/bin/tool", &secret, true)], &[]);
+    write_config(&fs, "1.2.0", &sha256);
+    let err = install(&fs).unwrap_err();
+    assert!(matches!(err, Error::ExtractArchive(..)), "{err}");
+    assert!(!dir.join("1.2.0").unwrap().exists());
+}
@@ -1,7 +1,11 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
 
 use logix::{
-    managed_file::{FileStatus, ManagedFile},
+    error::Error,
+    managed_file::{FileStatus, ManagedFile, Owner},
     Logix,
 };
 
@@ -40,6 +44,7 @@ struct WantStatus {
     local_added: Vec<ManagedFile>,
     logix_added: Vec<ManagedFile>,
     up_to_date: Vec<ManagedFile>,
+    link_differs: Vec<ManagedFile>,
 }
 
 impl WantStatus {
@@ -53,6 +58,7 @@ impl WantStatus {
                 local_added,
                 logix_added,
                 up_to_date,
+                link_differs,
             } = self;
 
             for file in modified {
@@ -74,6 +80,10 @@ impl WantStatus {
             for file in up_to_date {
                 assert_eq!(files.insert(file, FileStatus::UpToDate), None);
             }
+
+            for file in link_differs {
+                assert_eq!(files.insert(file, FileStatus::LinkDiffers), None);
+            }
         }
 
         for (status, file) in logix.calculate_config_status().unwrap() {
@@ -104,6 +114,7 @@ fn no_files() {
         local_added: vec![],
         logix_added: vec![],
         up_to_date: vec![],
+        link_differs: vec![],
     };
 
     assert!(want.assert_eq(&logix));
@@ -134,3 +145,272 @@ fn no_files() {
     fs.write_config_file("helix/runtime/whatever.txt", "# Dummy file");
     assert!(want.assert_eq(&logix));
 }
+
+#[test]
+fn file_index_invalidation() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let logix = fs.load_logix();
+    let old_mtime = SystemTime::now() - Duration::from_secs(3600);
+    let mut want = WantStatus {
+        modified: vec![],
+        missing: vec![],
+        local_added: vec![],
+        logix_added: vec![],
+        up_to_date: vec![fs.managed_logix_config("helix", "helix/config.toml")],
+        link_differs: vec![],
+    };
+
+    // Files that were modified recently are not indexed, so they are always read
+    fs.write_config_file("helix/config.toml", "theme = \"dark\"");
+    fs.write_config_file("logix/config/helix/config.toml", "theme = \"dark\"");
+    assert!(want.assert_eq(&logix));
+    assert!(fs.cache_file_exists("logix/file-index.json"));
+
+    // Older files are indexed, so a change that keeps size and mtime is not noticed
+    fs.set_config_file_mtime("helix/config.toml", old_mtime);
+    fs.set_config_file_mtime("logix/config/helix/config.toml", old_mtime);
+    assert!(want.assert_eq(&logix));
+    fs.write_config_file("helix/config.toml", "theme = \"lite\"");
+    fs.set_config_file_mtime("helix/config.toml", old_mtime);
+    assert!(want.assert_eq(&logix));
+
+    // Replacing the file gives it a new inode which invalidates the entry
+    fs.replace_config_file("helix/config.toml", "theme = \"lite\"");
+    fs.set_config_file_mtime("helix/config.toml", old_mtime);
+    want.modified.extend(want.up_to_date.pop());
+    assert!(want.assert_eq(&logix));
+
+    // A new mtime invalidates the entry
+    fs.write_config_file("helix/config.toml", "theme = \"dark\"");
+    want.up_to_date.extend(want.modified.pop());
+    assert!(want.assert_eq(&logix));
+    fs.set_config_file_mtime("helix/config.toml", old_mtime);
+    assert!(want.assert_eq(&logix));
+
+    // A new size invalidates the entry, even if the mtime is the same
+    fs.write_config_file("helix/config.toml", "theme = \"darker\"");
+    fs.set_config_file_mtime("helix/config.toml", old_mtime);
+    want.modified.extend(want.up_to_date.pop());
+    assert!(want.assert_eq(&logix));
+
+    // A corrupt index is discarded
+    fs.write_cache_file("logix/file-index.json", "{ not json");
+    assert!(want.assert_eq(&logix));
+}
+
+#[test]
+fn conflicting_owners() {
+    static CONFLICTING_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      helix: Custom {
+        source: GitHub {
+          owner: "helix-editor"
+          repo: "helix"
+        }
+        config_dir: User {
+          package_name: "helix"
+        }
+      }
+      helix-nightly: Custom {
+        source: GitHub {
+          owner: "helix-editor"
+          repo: "helix"
+        }
+        config_dir: User {
+          package_name: "helix"
+          PRIORITY
+        }
+      }
+    }
+  }
+}
+"#;
+
+    // Both packages claim the file with the same priority
+    let fs = helper::TestFs::new(&CONFLICTING_LOGIX.replace("PRIORITY", ""));
+    fs.write_config_file("helix/config.toml", "# Dummy config");
+    let logix = fs.load_logix();
+    match logix.calculate_config_status() {
+        Err(Error::ConflictingOwners(path, first, second)) => {
+            assert_eq!(path.file_name().unwrap(), "config.toml");
+            assert_eq!(first, Owner::Package("helix".into()));
+            assert_eq!(second, Owner::Package("helix-nightly".into()));
+        }
+        Err(e) => panic!("Got unexpected error: {e}"),
+        Ok(_) => panic!("Expected an error"),
+    }
+
+    // The package with the highest priority wins
+    let fs = helper::TestFs::new(&CONFLICTING_LOGIX.replace("PRIORITY", "priority: 1"));
+    fs.write_config_file("helix/config.toml", "# Dummy config");
+    let logix = fs.load_logix();
+    let want = WantStatus {
+        modified: vec![],
+        missing: vec![],
+        local_added: vec![fs.managed_logix_config("helix-nightly", "helix/config.toml")],
+        logix_added: vec![],
+        up_to_date: vec![],
+        link_differs: vec![],
+    };
+    assert!(want.assert_eq(&logix));
+
+    // The priority also decides the owner of paths that can't be read
+    #[cfg(unix)]
+    {
+        fs.create_too_long_config_dir("helix/deep");
+        let status = logix.calculate_config_status().map(Vec::from_iter).unwrap();
+        assert_eq!(status.len(), 2, "{status:#?}");
+        match &status[1] {
+            (FileStatus::ErrorReadingLocal(_), file) => {
+                assert_eq!(*file.owner(), Owner::Package("helix-nightly".into()));
+            }
+            unk => panic!("Got unexpected status {unk:?}"),
+        }
+    }
+}
+
+#[test]
+fn highest_priority_added_last() {
+    static THREE_OWNERS_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      helix: Custom {
+        source: GitHub {
+          owner: "helix-editor"
+          repo: "helix"
+        }
+        config_dir: User {
+          package_name: "helix"
+        }
+      }
+      helix-nightly: Custom {
+        source: GitHub {
+          owner: "helix-editor"
+          repo: "helix"
+        }
+        config_dir: User {
+          package_name: "helix"
+        }
+      }
+      helix-dev: Custom {
+        source: GitHub {
+          owner: "helix-editor"
+          repo: "helix"
+        }
+        config_dir: User {
+          package_name: "helix"
+          priority: 1
+        }
+      }
+    }
+  }
+}
+"#;
+
+    // The two first owners are tied, but the last one has a higher priority
+    let fs = helper::TestFs::new(THREE_OWNERS_LOGIX);
+    fs.write_config_file("helix/config.toml", "# Dummy config");
+    let logix = fs.load_logix();
+    let want = WantStatus {
+        modified: vec![],
+        missing: vec![],
+        local_added: vec![fs.managed_logix_config("helix-dev", "helix/config.toml")],
+        logix_added: vec![],
+        up_to_date: vec![],
+        link_differs: vec![],
+    };
+    assert!(want.assert_eq(&logix));
+}
+
+#[cfg(unix)]
+#[test]
+fn unreadable_directory() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let logix = fs.load_logix();
+    fs.write_config_file("helix/config.toml", "# Dummy config");
+    fs.create_too_long_config_dir("helix/deep");
+
+    let status = logix.calculate_config_status().map(Vec::from_iter).unwrap();
+    assert_eq!(status.len(), 2, "{status:#?}");
+    assert_eq!(
+        status[0],
+        (
+            FileStatus::LocalAdded,
+            fs.managed_logix_config("helix", "helix/config.toml")
+        )
+    );
+    match &status[1] {
+        (FileStatus::ErrorReadingLocal(_), file) => {
+            assert!(
+                file.local_path()
+                    .unwrap()
+                    .starts_with(fs.config_path("helix/deep")),
+                "{file:?}"
+            );
+        }
+        unk => panic!("Got unexpected status {unk:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let logix = fs.load_logix();
+    let mut want = WantStatus {
+        modified: vec![],
+        missing: vec![],
+        local_added: vec![],
+        logix_added: vec![],
+        up_to_date: vec![
+            fs.managed_logix_config("helix", "helix/themes/dark.toml"),
+            fs.managed_logix_config("helix", "helix/themes/light.toml"),
+        ],
+        link_differs: vec![],
+    };
+
+    fs.write_config_file("helix/themes/dark.toml", "# Dark theme");
+    fs.write_config_file("helix/themes/light.toml", "# Light theme");
+    fs.write_config_file("logix/config/helix/themes/dark.toml", "# Dark theme");
+    fs.write_config_file("logix/config/helix/themes/light.toml", "# Light theme");
+
+    // Links are compared by target, not by the content they point to
+    fs.symlink_config_file("helix/current-theme.toml", "themes/dark.toml");
+    want.local_added
+        .push(fs.managed_logix_config("helix", "helix/current-theme.toml"));
+    assert!(want.assert_eq(&logix));
+
+    fs.symlink_config_file("logix/config/helix/current-theme.toml", "themes/dark.toml");
+    want.up_to_date.extend(want.local_added.pop());
+    assert!(want.assert_eq(&logix));
+
+    fs.symlink_config_file("helix/current-theme.toml", "themes/light.toml");
+    want.link_differs.extend(want.up_to_date.pop());
+    assert!(want.assert_eq(&logix));
+
+    // A regular file is never the same as a link
+    fs.remove_config_file("helix/current-theme.toml");
+    fs.write_config_file("helix/current-theme.toml", "# Dark theme");
+    assert!(want.assert_eq(&logix));
+
+    // Links to directories are tracked as links, and dangling links are still tracked
+    fs.symlink_config_file("helix/current-theme.toml", "themes/dark.toml");
+    fs.symlink_config_file("helix/themes-link", "themes");
+    fs.symlink_config_file("logix/config/helix/themes-link", "themes");
+    fs.symlink_config_file("helix/dangling.toml", "does-not-exist.toml");
+    want.up_to_date.extend(want.link_differs.pop());
+    want.up_to_date
+        .push(fs.managed_logix_config("helix", "helix/themes-link"));
+    want.local_added
+        .push(fs.managed_logix_config("helix", "helix/dangling.toml"));
+    assert!(want.assert_eq(&logix));
+}
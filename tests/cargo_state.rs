@@ -0,0 +1,300 @@
+use logix::{
+    config::{CargoFlag, CargoInstallOptions, CargoInstaller, PackageDefaults, RustCrateSource},
+    managed_package::PackageVersion,
+    package_provider::CargoState,
+    system_state::{
+        CrateBuild, CrateFeatures, CrateSource, CrateSpec, GitReference, GitSource, InstalledCrate,
+        SystemState,
+    },
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+  }
+}
+"#;
+
+static CRATES2_JSON: &str = r#"{
+  "installs": {
+    "ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)": {
+      "version_req": null,
+      "bins": ["rg"],
+      "features": ["pcre2"],
+      "all_features": false,
+      "no_default_features": false,
+      "profile": "release",
+      "target": "x86_64-unknown-linux-gnu",
+      "rustc": "rustc 1.81.0 (eeb90cda1 2024-09-04)"
+    },
+    "helix-term 24.7.0 (git+https://github.com/helix-editor/helix?branch=master#0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e)": {
+      "version_req": null,
+      "bins": ["hx"],
+      "features": [],
+      "all_features": false,
+      "no_default_features": true,
+      "profile": "opt",
+      "target": "x86_64-unknown-linux-gnu",
+      "rustc": "rustc 1.81.0 (eeb90cda1 2024-09-04)"
+    }
+  }
+}"#;
+
+#[test]
+fn read_crates2_json() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_home_file(".cargo/.crates2.json", CRATES2_JSON);
+    let env = fs.init_env();
+    let state = SystemState::init(&env).unwrap();
+
+    let mut crates = state
+        .provider::<CargoState>()
+        .unwrap()
+        .installed_crates()
+        .cloned()
+        .collect::<Vec<_>>();
+    crates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    assert_eq!(
+        crates,
+        [
+            InstalledCrate {
+                name: "helix-term".into(),
+                version: semver::Version::new(24, 7, 0),
+                source: CrateSource::Git(GitSource {
+                    url: "https://github.com/helix-editor/helix".into(),
+                    reference: GitReference::Branch("master".into()),
+                    commit: "0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e".into(),
+                }),
+                bins: vec!["hx".into()],
+                build: Some(CrateBuild {
+                    features: CrateFeatures {
+                        features: vec![],
+                        all_features: false,
+                        no_default_features: true,
+                    },
+                    profile: "opt".into(),
+                }),
+            },
+            InstalledCrate {
+                name: "ripgrep".into(),
+                version: semver::Version::new(14, 1, 0),
+                source: CrateSource::Registry(
+                    "https://github.com/rust-lang/crates.io-index".into()
+                ),
+                bins: vec!["rg".into()],
+                build: Some(CrateBuild {
+                    features: CrateFeatures {
+                        features: vec!["pcre2".into()],
+                        all_features: false,
+                        no_default_features: false,
+                    },
+                    profile: "release".into(),
+                }),
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_git_source() {
+    let url = "https://github.com/helix-editor/helix";
+    let commit = "0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e";
+    let git = |reference| {
+        Some(GitSource {
+            url: url.into(),
+            reference,
+            commit: commit.into(),
+        })
+    };
+
+    for (source, expected) in [
+        (
+            format!("{url}?tag=24.07#{commit}"),
+            git(GitReference::Tag("24.07".into())),
+        ),
+        (
+            format!("{url}?rev=0ee5850#{commit}"),
+            git(GitReference::Rev("0ee5850".into())),
+        ),
+        (
+            format!("{url}?branch=master#{commit}"),
+            git(GitReference::Branch("master".into())),
+        ),
+        (format!("{url}#{commit}"), git(GitReference::DefaultBranch)),
+        // The commit is always recorded, and only known references are understood
+        (format!("{url}?branch=master"), None),
+        (format!("{url}?path=helix-term#{commit}"), None),
+        (format!("{url}?branch#{commit}"), None),
+    ] {
+        assert_eq!(GitSource::parse(&source), expected, "{source}");
+    }
+}
+
+static BRANCH_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      helix: RustCrate {
+        crate_name: "helix-term"
+        source: GithubBranch {
+          owner: "helix-editor"
+          repo: "helix"
+          branch: "BRANCH"
+        }
+      }
+    }
+  }
+}
+"#;
+
+static DEFAULT_BRANCH_CRATES2_JSON: &str = r#"{
+  "installs": {
+    "helix-term 24.7.0 (git+https://github.com/helix-editor/helix#0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e)": {
+      "bins": ["hx"],
+      "profile": "release"
+    }
+  }
+}"#;
+
+static HELIX_COMMIT: &str = r#"{
+  "sha": "0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e",
+  "commit": { "author": { "date": "2024-09-28T15:37:35Z" } }
+}"#;
+
+#[test]
+fn installed_from_default_branch() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_home_file(".cargo/.crates2.json", DEFAULT_BRANCH_CRATES2_JSON);
+    fs.write_cache_file(
+        "logix/github-repo/helix-editor/helix/info.json",
+        r#"{ "default_branch": "master" }"#,
+    );
+    fs.write_cache_file(
+        "logix/github-repo/helix-editor/helix/commits/0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e.json",
+        HELIX_COMMIT,
+    );
+    for branch in ["master", "next"] {
+        fs.write_cache_file(
+            &format!("logix/github-repo/helix-editor/helix/branches/{branch}.json"),
+            &format!(r#"{{ "commit": {HELIX_COMMIT} }}"#),
+        );
+    }
+    let installed = PackageVersion::Commit {
+        id: "0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e".into(),
+        date: Some("2024-09-28T15:37:35Z".parse().unwrap()),
+    };
+
+    // Installed without a branch, which is the default branch of the remote
+    for (branch, expected) in [("master", installed), ("next", PackageVersion::None)] {
+        fs.write_config_file("logix/root.logix", &BRANCH_LOGIX.replace("BRANCH", branch));
+        let logix = fs.load_logix();
+        let state = SystemState::init(logix.env()).unwrap();
+        let status = logix
+            .find_package("helix")
+            .unwrap()
+            .calculate_status(&state)
+            .unwrap();
+        assert_eq!(status.installed_version, expected, "{branch}");
+    }
+}
+
+fn install_args(
+    source: Option<&RustCrateSource>,
+    options: &CargoInstallOptions,
+    version: &PackageVersion,
+    binstall_available: bool,
+) -> (&'static str, String) {
+    let spec = CrateSpec::new(
+        "ripgrep",
+        source,
+        Some(options),
+        None,
+        PackageDefaults::EMPTY,
+    )
+    .unwrap();
+    let (what, args) = spec.install_args(version, binstall_available);
+    (what, args.join(" "))
+}
+
+#[test]
+fn binstall_or_cargo_install() {
+    let binstall = CargoInstallOptions {
+        installer: Some(CargoInstaller::Binstall),
+        features: None,
+        flags: Some(vec![CargoFlag::Locked]),
+        bins: None,
+        profile: None,
+        toolchain: None,
+    };
+    let version = PackageVersion::Semver("14.1.0".parse().unwrap());
+
+    // Prebuilt binaries are used when available
+    assert_eq!(
+        install_args(None, &binstall, &version, true),
+        (
+            "cargo binstall",
+            "binstall --no-confirm --locked ripgrep@14.1.0".into()
+        )
+    );
+    assert_eq!(
+        install_args(None, &binstall, &PackageVersion::None, true),
+        (
+            "cargo binstall",
+            "binstall --no-confirm --locked ripgrep".into()
+        )
+    );
+
+    // Otherwise the crate is built using the same options
+    assert_eq!(
+        install_args(None, &binstall, &version, false),
+        (
+            "cargo install",
+            "install --locked --version =14.1.0 ripgrep".into()
+        )
+    );
+
+    // Crates that need to be built from source never use binstall
+    let features = CargoInstallOptions {
+        installer: Some(CargoInstaller::Binstall),
+        features: Some(vec!["pcre2".into()]),
+        flags: None,
+        bins: None,
+        profile: None,
+        toolchain: Some("nightly".into()),
+    };
+    assert_eq!(
+        install_args(None, &features, &version, true),
+        (
+            "cargo install",
+            "+nightly install --features pcre2 --version =14.1.0 ripgrep".into()
+        )
+    );
+    let source = RustCrateSource::GithubTag {
+        owner: "BurntSushi".into(),
+        repo: "ripgrep".into(),
+        tag: "14.1.0".into(),
+    };
+    assert_eq!(
+        install_args(Some(&source), &binstall, &PackageVersion::None, true),
+        (
+            "cargo install",
+            "install --locked --git https://github.com/BurntSushi/ripgrep.git --tag 14.1.0 ripgrep"
+                .into()
+        )
+    );
+
+    // The configured installer is respected
+    assert_eq!(
+        install_args(None, CargoInstallOptions::EMPTY, &version, true),
+        ("cargo install", "install --version =14.1.0 ripgrep".into())
+    );
+}
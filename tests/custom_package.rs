@@ -0,0 +1,225 @@
+use logix::{
+    error::Error, installed_packages::InstalledKind, managed_package::PackageVersion,
+    system_state::SystemState,
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      tool: Custom {
+        source: GitHub {
+          owner: "zeldor"
+          repo: "tool"
+        }
+        local_dir: "src/tool"
+      }
+    }
+  }
+}
+"#;
+
+static REMOTE_INFO: &str = r#"{ "default_branch": "main" }"#;
+
+static REMOTE_BRANCH: &str = r#"{
+  "commit": {
+    "sha": "0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e",
+    "commit": { "author": { "date": "2024-09-28T15:37:35Z" } }
+  }
+}"#;
+
+#[test]
+fn downloaded_version() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_cache_file("logix/github-repo/zeldor/tool/info.json", REMOTE_INFO);
+    fs.write_cache_file(
+        "logix/github-repo/zeldor/tool/branches/main.json",
+        REMOTE_BRANCH,
+    );
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+    let package = logix.find_package("tool").unwrap();
+
+    // Nothing is downloaded before the sources are cloned
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.downloaded_version, PackageVersion::None);
+    assert_eq!(status.installed_version, PackageVersion::None);
+    assert!(!status.is_downloaded_not_built());
+
+    let dir = fs.home_path("src/tool");
+    std::fs::create_dir_all(&dir).unwrap();
+    helper::git(&dir, &["init", "--quiet"]);
+    helper::git(
+        &dir,
+        &["commit", "--quiet", "--allow-empty", "-m", "Initial"],
+    );
+    let id = helper::git(&dir, &["rev-parse", "HEAD"]);
+
+    // The checked out commit is downloaded, but it was never installed
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(
+        status.downloaded_version,
+        PackageVersion::Commit {
+            id: id.trim().into(),
+            date: Some("2024-10-01T10:00:00Z".parse().unwrap()),
+        }
+    );
+    assert_eq!(status.installed_version, PackageVersion::None);
+    assert!(status.is_downloaded_not_built());
+}
+
+static GIT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      tool: Custom {
+        source: Git {
+          url: "URL"
+          branch: "next"
+        }
+        local_dir: "src/tool"
+        build: ShellCommand {
+          command: "git log -1 --format=%s > built.txt"
+        }
+        install: ShellCommand {
+          command: "cp built.txt ../installed.txt"
+        }
+      }
+    }
+  }
+}
+"#;
+
+#[test]
+fn install_branch() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let remote = fs.home_path("remote/tool");
+    std::fs::create_dir_all(&remote).unwrap();
+    helper::git(&remote, &["init", "--quiet", "--initial-branch=main"]);
+    helper::git(
+        &remote,
+        &["commit", "--quiet", "--allow-empty", "-m", "Main"],
+    );
+    helper::git(&remote, &["checkout", "--quiet", "-b", "next"]);
+    helper::git(
+        &remote,
+        &["commit", "--quiet", "--allow-empty", "-m", "Next"],
+    );
+    let id = helper::git(&remote, &["rev-parse", "HEAD"]);
+    helper::git(&remote, &["checkout", "--quiet", "main"]);
+    fs.write_config_file(
+        "logix/root.logix",
+        &GIT_LOGIX.replace("URL", &helper::file_url(&remote)),
+    );
+
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    let package = logix.find_package("tool").unwrap();
+
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, PackageVersion::None);
+    assert!(status.need_update());
+
+    // The declared branch is built, not the default branch of the remote
+    let expected = PackageVersion::Commit {
+        id: id.trim().into(),
+        date: Some("2024-10-01T10:00:00Z".parse().unwrap()),
+    };
+    let version = package
+        .install_update(&mut state, status.target_version())
+        .unwrap();
+    assert_eq!(version, expected);
+    assert_eq!(
+        std::fs::read_to_string(fs.home_path("src/installed.txt")).unwrap(),
+        "Next\n"
+    );
+
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, expected);
+    assert!(!status.need_update());
+
+    // Without a target version the head of the branch is installed
+    let version = package
+        .install_update(&mut state, &PackageVersion::None)
+        .unwrap();
+    assert_eq!(version, expected);
+}
+
+#[test]
+fn uninstall_keeps_record() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let dir = fs.home_path("src/old-tool");
+    fs.write_home_file("src/old-tool/Makefile", "all:\n");
+    fs.write_home_file(
+        ".local/state/logix/installed.json",
+        &format!(
+            r#"{{
+  "version": 1,
+  "packages": [
+    {{ "name": "old-tool", "kind": {{ "Custom": {{ "local_dir": "{}" }} }}, "version": {{ "Commit": {{ "id": "1111111111111111111111111111111111111111" }} }} }}
+  ]
+}}"#,
+            dir.display()
+        ),
+    );
+
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    let kind = InstalledKind::Custom {
+        local_dir: dir.to_path_buf(),
+    };
+
+    // The installed files are unknown, so it is only forgotten once the sources are removed
+    let err = state.uninstall(&kind).unwrap_err();
+    assert!(matches!(err, Error::CustomPackageUninstall(_)), "{err}");
+    assert!(state.installed_package("old-tool", &kind).is_some());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    state.uninstall(&kind).unwrap();
+    assert!(state.installed_package("old-tool", &kind).is_none());
+}
+
+#[test]
+fn failed_step() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let remote = fs.home_path("remote/tool");
+    std::fs::create_dir_all(&remote).unwrap();
+    helper::git(&remote, &["init", "--quiet", "--initial-branch=next"]);
+    helper::git(
+        &remote,
+        &["commit", "--quiet", "--allow-empty", "-m", "Broken"],
+    );
+    fs.write_config_file(
+        "logix/root.logix",
+        &GIT_LOGIX
+            .replace("URL", &helper::file_url(&remote))
+            .replace("git log -1 --format=%s > built.txt", "exit 3"),
+    );
+
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    let package = logix.find_package("tool").unwrap();
+
+    // The failing step is named and nothing is recorded as installed
+    let err = package
+        .install_update(&mut state, &PackageVersion::None)
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::CustomPackageStep(name, "build", _) if name == "tool"),
+        "{err}"
+    );
+    assert!(!fs.home_path("src/installed.txt").exists());
+    assert_eq!(
+        package.calculate_status(&state).unwrap().installed_version,
+        PackageVersion::None
+    );
+}
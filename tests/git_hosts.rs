@@ -0,0 +1,254 @@
+use std::process::Command;
+
+use logix::{managed_package::PackageVersion, system_state::SystemState};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      tool: RustCrate {
+        crate_name: "tool"
+        source: GitLabBranch {
+          base_url: "https://gitlab.example.com/"
+          project: "zeldor/tools/tool"
+          branch: "main"
+        }
+      }
+      theme: Custom {
+        source: Codeberg {
+          owner: "zeldor"
+          repo: "theme"
+        }
+      }
+    }
+  }
+}
+"#;
+
+static CRATES2_JSON: &str = r#"{
+  "installs": {
+    "tool 0.1.0 (git+https://gitlab.example.com/zeldor/tools/tool.git?branch=main#5c4b5e8a1f0d3c9e2b7a6f4d8e1c0b9a7f6e5d4c)": {
+      "bins": ["tool"],
+      "profile": "release"
+    }
+  }
+}"#;
+
+static GITLAB_INSTALLED: &str = r#"{
+  "id": "5c4b5e8a1f0d3c9e2b7a6f4d8e1c0b9a7f6e5d4c",
+  "authored_date": "2024-09-01T10:00:00.000+02:00"
+}"#;
+
+static GITLAB_MAIN: &str = r#"{
+  "id": "9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b",
+  "authored_date": "2024-10-01T10:00:00.000+02:00"
+}"#;
+
+static CODEBERG_INFO: &str = r#"{ "default_branch": "trunk" }"#;
+
+static CODEBERG_TRUNK: &str = r#"{
+  "sha": "0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e",
+  "commit": { "author": { "date": "2024-09-28T15:37:35Z" } }
+}"#;
+
+fn commit(id: &str, date: Option<&str>) -> PackageVersion {
+    PackageVersion::Commit {
+        id: id.into(),
+        date: date.map(|v| v.parse().unwrap()),
+    }
+}
+
+#[test]
+fn gitlab_and_codeberg() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_home_file(".cargo/.crates2.json", CRATES2_JSON);
+    fs.write_cache_file(
+        "logix/gitlab-project/gitlab.example.com/zeldor/tools/tool/commits/5c4b5e8a1f0d3c9e2b7a6f4d8e1c0b9a7f6e5d4c.json",
+        GITLAB_INSTALLED,
+    );
+    fs.write_cache_file(
+        "logix/gitlab-project/gitlab.example.com/zeldor/tools/tool/commits/main.json",
+        GITLAB_MAIN,
+    );
+    fs.write_cache_file(
+        "logix/gitea-repo/codeberg.org/zeldor/theme/info.json",
+        CODEBERG_INFO,
+    );
+    fs.write_cache_file(
+        "logix/gitea-repo/codeberg.org/zeldor/theme/commits/trunk.json",
+        CODEBERG_TRUNK,
+    );
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    let status = logix
+        .find_package("tool")
+        .unwrap()
+        .calculate_status(&state)
+        .unwrap();
+    assert_eq!(
+        status.installed_version,
+        commit(
+            "5c4b5e8a1f0d3c9e2b7a6f4d8e1c0b9a7f6e5d4c",
+            Some("2024-09-01T08:00:00Z")
+        )
+    );
+    assert_eq!(
+        status.latest_version,
+        commit(
+            "9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b",
+            Some("2024-10-01T08:00:00Z")
+        )
+    );
+    assert!(status.need_update());
+
+    let status = logix
+        .find_package("theme")
+        .unwrap()
+        .calculate_status(&state)
+        .unwrap();
+    assert_eq!(
+        status.latest_version,
+        commit(
+            "0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e",
+            Some("2024-09-28T15:37:35Z")
+        )
+    );
+}
+
+fn git(dir: &std::path::Path, args: &[&str]) -> String {
+    let res = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "-c",
+            "user.name=Zeldon Kingly",
+            "-c",
+            "user.email=zeldor@example.com",
+        ])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(res.status.success(), "{res:?}");
+    String::from_utf8(res.stdout).unwrap()
+}
+
+#[test]
+fn plain_git_remote() {
+    let remote = tempfile::TempDir::new().unwrap();
+    git(remote.path(), &["init", "--quiet", "--initial-branch=main"]);
+    git(
+        remote.path(),
+        &["commit", "--quiet", "--allow-empty", "-m", "Initial"],
+    );
+    let main = git(remote.path(), &["rev-parse", "HEAD"]);
+    git(remote.path(), &["checkout", "--quiet", "-b", "next"]);
+    git(
+        remote.path(),
+        &["commit", "--quiet", "--allow-empty", "-m", "Next"],
+    );
+    let next = git(remote.path(), &["rev-parse", "HEAD"]);
+    git(remote.path(), &["checkout", "--quiet", "main"]);
+
+    let fs = helper::TestFs::new(&format!(
+        r#"
+Logix {{
+  home: UserProfile {{
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {{
+      stable: Custom {{
+        source: Git {{
+          url: {url:?}
+        }}
+      }}
+      next: Custom {{
+        source: Git {{
+          url: {url:?}
+          branch: "next"
+        }}
+      }}
+    }}
+  }}
+}}
+"#,
+        url = remote.path().display().to_string(),
+    ));
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    // The date is unknown as `git ls-remote` only reports the id
+    let status = logix
+        .find_package("stable")
+        .unwrap()
+        .calculate_status(&state)
+        .unwrap();
+    assert_eq!(status.latest_version, commit(main.trim(), None));
+
+    let status = logix
+        .find_package("next")
+        .unwrap()
+        .calculate_status(&state)
+        .unwrap();
+    assert_eq!(status.latest_version, commit(next.trim(), None));
+    assert!(!status.is_downloaded_not_built());
+}
+
+static GITEA_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      theme: RustCrate {
+        crate_name: "theme"
+        source: GiteaBranch {
+          base_url: "URL"
+          owner: "zeldor"
+          repo: "theme"
+          branch: "fix#1"
+        }
+      }
+    }
+  }
+}
+"#;
+
+#[test]
+fn gitea_branch_is_encoded() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_home_file(
+        "gitea/api/v1/repos/zeldor/theme/git/commits/fix#1",
+        CODEBERG_TRUNK,
+    );
+    fs.write_config_file(
+        "logix/root.logix",
+        &GITEA_LOGIX.replace("URL", &helper::file_url(&fs.home_path("gitea"))),
+    );
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    // The `#` would otherwise end the path of the url
+    let status = logix
+        .find_package("theme")
+        .unwrap()
+        .calculate_status(&state)
+        .unwrap();
+    assert_eq!(
+        status.latest_version,
+        commit(
+            "0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e",
+            Some("2024-09-28T15:37:35Z")
+        )
+    );
+}
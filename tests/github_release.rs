@@ -0,0 +1,233 @@
+use logix::{
+    error::Error, file_index::ContentHash, managed_package::PackageVersion,
+    system_state::SystemState,
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      ripgrep: GitHubRelease {
+        owner: "BurntSushi"
+        repo: "ripgrep"
+        bin: "rg"
+        checksum_asset: "{asset}.sha256"
+        version: "^13"
+      }
+    }
+  }
+}
+"#;
+
+static RELEASES: &str = r#"[
+  { "tag_name": "15.0.0-rc.1", "draft": false, "prerelease": true, "assets": [] },
+  { "tag_name": "14.1.1", "draft": false, "prerelease": false, "assets": [] },
+  { "tag_name": "nightly", "draft": false, "prerelease": false, "assets": [] },
+  { "tag_name": "13.0.0", "draft": false, "prerelease": false, "assets": [] },
+  { "tag_name": "12.1.1", "draft": false, "prerelease": false, "assets": [] },
+  { "tag_name": "16.0.0", "draft": true, "prerelease": false, "assets": [] }
+]"#;
+
+static INSTALLED_JSON: &str = r#"{
+  "version": 1,
+  "packages": [
+    { "name": "ripgrep", "kind": { "GitHubRelease": { "bin": "BIN" } }, "version": { "Semver": "12.1.1" } }
+  ]
+}"#;
+
+static DOWNLOAD_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      tool: GitHubRelease {
+        owner: "zeldor"
+        repo: "tool"
+      }
+      zipped: GitHubRelease {
+        owner: "zeldor"
+        repo: "zipped"
+      }
+    }
+  }
+}
+"#;
+
+fn semver(v: &str) -> PackageVersion {
+    PackageVersion::Semver(v.parse().unwrap())
+}
+
+#[test]
+fn release_versions() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_cache_file(
+        "logix/github-repo/BurntSushi/ripgrep/releases.json",
+        RELEASES,
+    );
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+    let package = logix.find_package("ripgrep").unwrap();
+
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, PackageVersion::None);
+    assert_eq!(status.latest_version, semver("13.0.0"));
+    assert_eq!(status.available_version, semver("14.1.1"));
+    assert!(status.need_update());
+
+    // The recorded version is only used while the binary exists
+    let bin = fs.home_path(".local/bin/rg");
+    fs.write_home_file(
+        ".local/state/logix/installed.json",
+        &INSTALLED_JSON.replace("BIN", bin.to_str().unwrap()),
+    );
+    let state = SystemState::init(logix.env()).unwrap();
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, PackageVersion::None);
+
+    fs.write_home_file(".local/bin/rg", "#!/bin/sh\n");
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, semver("12.1.1"));
+    assert!(status.need_update());
+}
+
+/// A release with the assets published in `downloads` of the home directory
+fn release(fs: &helper::TestFs, tag: &str, assets: &[&str]) -> String {
+    let assets = assets
+        .iter()
+        .map(|name| {
+            let url = helper::file_url(&fs.home_path(&format!("downloads/{tag}/{name}")));
+            format!(r#"{{ "name": "{name}", "browser_download_url": "{url}" }}"#)
+        })
+        .collect::<Vec<_>>();
+    format!(
+        r#"{{ "tag_name": "{tag}", "draft": false, "prerelease": false, "assets": [{}] }}"#,
+        assets.join(", ")
+    )
+}
+
+#[test]
+fn install_release() {
+    let fs = helper::TestFs::new(DOWNLOAD_LOGIX);
+    let host = format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH);
+
+    // Only the asset for the host is downloaded, the others don't exist
+    let host_asset = format!("tool-{host}.tar.gz");
+    let data = helper::tar_gz(&[
+        ("tool-1.0.0/README.md", "# Tool\n"),
+        ("tool-1.0.0/tool", "#!/bin/sh\necho 1.0.0\n"),
+    ]);
+    fs.write_home_bytes(&format!("downloads/1.0.0/{host_asset}"), &data);
+    fs.write_home_file(
+        "downloads/1.0.0/checksums.txt",
+        &format!(
+            "{}  tool-plan9-mips.tar.gz\n{}  {host_asset}\n",
+            "0".repeat(64),
+            ContentHash::of_bytes(&data).as_hex()
+        ),
+    );
+
+    // The published checksum doesn't match the asset of the next release
+    let data = helper::tar_gz(&[("tool-1.1.0/tool", "#!/bin/sh\necho 1.1.0\n")]);
+    fs.write_home_bytes(&format!("downloads/1.1.0/{host_asset}"), &data);
+    fs.write_home_file(
+        "downloads/1.1.0/checksums.txt",
+        &format!("{}  {host_asset}\n", "0".repeat(64)),
+    );
+
+    let assets = ["tool-plan9-mips.tar.gz", &host_asset, "checksums.txt"];
+    fs.write_cache_file(
+        "logix/github-repo/zeldor/tool/releases.json",
+        &format!(
+            "[{}, {}]",
+            release(&fs, "1.1.0", &assets),
+            release(&fs, "1.0.0", &assets)
+        ),
+    );
+
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    let package = logix.find_package("tool").unwrap();
+
+    let version = package
+        .install_update(&mut state, &semver("1.0.0"))
+        .unwrap();
+    assert_eq!(version, semver("1.0.0"));
+    let bin = fs.home_path(".local/bin/tool");
+    assert_eq!(
+        std::fs::read_to_string(&bin).unwrap(),
+        "#!/bin/sh\necho 1.0.0\n"
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(bin.metadata().unwrap().permissions().mode() & 0o777, 0o755);
+    }
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, semver("1.0.0"));
+
+    let err = package
+        .install_update(&mut state, &semver("1.1.0"))
+        .unwrap_err();
+    assert!(matches!(err, Error::ChecksumMismatch(..)), "{err}");
+    assert_eq!(
+        std::fs::read_to_string(&bin).unwrap(),
+        "#!/bin/sh\necho 1.0.0\n"
+    );
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, semver("1.0.0"));
+}
+
+#[test]
+fn install_zip_release() {
+    let fs = helper::TestFs::new(DOWNLOAD_LOGIX);
+    let host = format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH);
+
+    let host_asset = format!("zipped-{host}.zip");
+    let data = helper::zip(&[("bin/zipped", "#!/bin/sh\necho 2.0.0\n")]);
+    fs.write_home_bytes(&format!("downloads/2.0.0/{host_asset}"), &data);
+    fs.write_home_file(
+        &format!("downloads/2.0.0/{host_asset}.sha256"),
+        ContentHash::of_bytes(&data).as_hex(),
+    );
+
+    // Releases without a checksum are never installed
+    fs.write_home_bytes(&format!("downloads/2.1.0/{host_asset}"), &data);
+
+    fs.write_cache_file(
+        "logix/github-repo/zeldor/zipped/releases.json",
+        &format!(
+            "[{}, {}]",
+            release(&fs, "2.1.0", &[&host_asset]),
+            release(
+                &fs,
+                "2.0.0",
+                &[&host_asset, &format!("{host_asset}.sha256")]
+            )
+        ),
+    );
+
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    let package = logix.find_package("zipped").unwrap();
+
+    package
+        .install_update(&mut state, &semver("2.0.0"))
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(fs.home_path(".local/bin/zipped")).unwrap(),
+        "#!/bin/sh\necho 2.0.0\n"
+    );
+
+    let err = package
+        .install_update(&mut state, &semver("2.1.0"))
+        .unwrap_err();
+    assert!(matches!(err, Error::NoChecksumAsset(..)), "{err}");
+}
@@ -1,4 +1,7 @@
-use std::{rc::Rc, sync::Arc};
+// NOTE: Not all tests use all the helpers
+#![allow(dead_code)]
+
+use std::{io::Write, path::Path, rc::Rc, sync::Arc, time::SystemTime};
 
 use logix::{
     based_path::BasedPath,
@@ -47,11 +50,108 @@ impl TestFs {
     }
 
     pub fn write_home_file(&self, path: &str, data: &str) {
+        self.write_home_bytes(path, data.as_bytes());
+    }
+
+    pub fn write_home_bytes(&self, path: &str, data: &[u8]) {
         let path = self.inner.home.join(path).unwrap();
         std::fs::create_dir_all(path.parent().unwrap()).unwrap();
         std::fs::write(&path, data).unwrap();
     }
 
+    pub fn remove_config_file(&self, path: &str) {
+        std::fs::remove_file(self.inner.local_config.join(path).unwrap()).unwrap();
+    }
+
+    /// Create a symlink at `path` pointing to `target`, replacing any existing file
+    #[cfg(unix)]
+    pub fn symlink_config_file(&self, path: &str, target: &str) {
+        let path = self.inner.local_config.join(path).unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        if std::fs::symlink_metadata(&path).is_ok() {
+            std::fs::remove_file(&path).unwrap();
+        }
+        std::os::unix::fs::symlink(target, &path).unwrap();
+    }
+
+    /// Replace the file with a new one, this gives it a new inode
+    pub fn replace_config_file(&self, path: &str, data: &str) {
+        let path = self.inner.local_config.join(path).unwrap();
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, data).unwrap();
+        std::fs::rename(&tmp_path, &path).unwrap();
+    }
+
+    pub fn set_config_file_mtime(&self, path: &str, mtime: SystemTime) {
+        let path = self.inner.local_config.join(path).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+    }
+
+    /// Create nested directories under `path` until the full path is longer than
+    /// the OS allows, reading the deepest one fails even when running as root
+    pub fn create_too_long_config_dir(&self, path: &str) {
+        let path = self.inner.local_config.join(path).unwrap();
+        let name = "d".repeat(250);
+        std::fs::create_dir_all(&path).unwrap();
+
+        // NOTE: The tree is built from the bottom by moving it into a new parent,
+        // as the full path can't be used once it is too long
+        let mut top = path.join("0").unwrap();
+        std::fs::create_dir(&top).unwrap();
+        for i in 1..20 {
+            let parent = path.join(i.to_string()).unwrap();
+            std::fs::create_dir(&parent).unwrap();
+            std::fs::rename(&top, parent.join(&name).unwrap()).unwrap();
+            top = parent;
+        }
+        std::fs::rename(&top, path.join(&name).unwrap()).unwrap();
+    }
+
+    pub fn config_path(&self, path: &str) -> BasedPath {
+        self.inner.local_config.join(path).unwrap()
+    }
+
+    #[cfg(unix)]
+    pub fn set_config_file_mode(&self, path: &str, mode: u32) {
+        use std::os::unix::fs::PermissionsExt;
+        let path = self.inner.local_config.join(path).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    pub fn home_path(&self, path: &str) -> BasedPath {
+        self.inner.home.join(path).unwrap()
+    }
+
+    fn os_release_path(&self) -> FullPath {
+        FullPath::try_from(self.inner._root.path().join("etc/os-release")).unwrap()
+    }
+
+    /// Write the file used to detect the distribution, no distribution is detected without it
+    pub fn write_os_release(&self, data: &str) {
+        let path = self.os_release_path();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+
+    pub fn write_cache_file(&self, path: &str, data: &str) {
+        self.write_home_file(&format!(".cache/{path}"), data);
+    }
+
+    pub fn cache_file_exists(&self, path: &str) -> bool {
+        self.inner
+            .home
+            .join(".cache")
+            .unwrap()
+            .join(path)
+            .unwrap()
+            .exists()
+    }
+
     pub fn load_logix(&self) -> Loaded<Logix> {
         let env = self.init_env();
         Loaded {
@@ -65,6 +165,7 @@ impl TestFs {
             inner: self.inner.clone(),
             value: Env::builder()
                 .home_dir(self.inner.home.as_full_path().clone())
+                .os_release(self.os_release_path())
                 .build()
                 .unwrap(),
         }
@@ -109,3 +210,56 @@ impl<T> std::ops::Deref for Loaded<T> {
         &self.value
     }
 }
+
+/// Build a `.tar.gz` archive of executable files given as `(path, content)`
+pub fn tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    for (path, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, data.as_bytes())
+            .unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
+/// Build a `.zip` archive of executable files given as `(path, content)`
+pub fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default().unix_permissions(0o755);
+    for (path, data) in files {
+        writer.start_file(*path, options).unwrap();
+        writer.write_all(data.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+/// The `file://` url of a local file, used instead of downloading from a server
+pub fn file_url(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+/// Run git in `dir` with a fixed author and date, returns the output
+pub fn git(dir: &Path, args: &[&str]) -> String {
+    let res = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "-c",
+            "user.name=Zeldon Kingly",
+            "-c",
+            "user.email=zeldor@example.com",
+        ])
+        .args(args)
+        .env("GIT_AUTHOR_DATE", "2024-10-01T12:00:00+02:00")
+        .output()
+        .unwrap();
+    assert!(res.status.success(), "{res:?}");
+    String::from_utf8(res.stdout).unwrap()
+}
@@ -0,0 +1,218 @@
+use logix::{
+    installed_packages::InstalledKind,
+    managed_package::PackageVersion,
+    package_provider::{GoState, PythonState},
+    system_state::SystemState,
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      prettier: NpmPackage {
+        version: "^3"
+      }
+      gopls: GoPackage {
+        package_path: "golang.org/x/tools/gopls"
+      }
+      black: PythonTool {
+        package_name: "Black"
+        installer: Uv
+      }
+    }
+  }
+}
+"#;
+
+static PRETTIER_PACKAGE_JSON: &str = r#"{
+  "name": "prettier",
+  "version": "3.3.2"
+}"#;
+
+static PRETTIER_VERSIONS: &str = r#"["2.8.8", "3.3.2", "3.3.3", "4.0.0-alpha.8", "4.0.0"]"#;
+
+static GOPLS_VERSIONS: &str = r#"["v0.15.3", "v0.16.1", "v0.16.2"]"#;
+
+static GO_VERSION_M: &str = "/home/zeldor/go/bin/gopls: go1.23.2
+\tpath\tgolang.org/x/tools/gopls
+\tmod\tgolang.org/x/tools/gopls\tv0.16.2\th1:K1z03MlikHfaMTtG01cUeL5FAOTJnITuNe0TWOcg8tM=
+\tdep\tgithub.com/BurntSushi/toml\tv1.2.1\th1:9F2/+DoOYIOksmaJFPw1tGFy1eDnIJXg+UHjuD8lTak=
+\tdep\tgolang.org/x/mod\tv0.20.0\th1:utOm6MM3R3dnawAiJgn0y+xR1bvGgCZDaNs8rWQXxvY=
+\tdep\tgolang.org/x/sync\tv0.8.0\th1:3NFvSEYkUoMifnESzZl15y791HH1qU2xm6HROZDp2fY=
+\tbuild\t-buildmode=exe
+\tbuild\t-compiler=gc
+\tbuild\tCGO_ENABLED=1
+\tbuild\tGOARCH=amd64
+\tbuild\tGOOS=linux
+\tbuild\tGOAMD64=v1
+";
+
+static GO_VERSION_M_DEVEL: &str = "/home/zeldor/go/bin/tool: go1.23.2
+\tpath\tgithub.com/zeldor/tool/cmd/tool
+\tmod\tgithub.com/zeldor/tool\t(devel)\t
+\tbuild\t-buildmode=exe
+\tbuild\tvcs=git
+\tbuild\tvcs.revision=3333333333333333333333333333333333333333
+";
+
+static PIPX_LIST_JSON: &str = r#"{
+    "pipx_spec_version": "0.1",
+    "venvs": {
+        "black": {
+            "metadata": {
+                "injected_packages": {},
+                "main_package": {
+                    "app_paths": [
+                        {
+                            "__Path__": "/home/zeldor/.local/share/pipx/venvs/black/bin/black",
+                            "__type__": "Path"
+                        }
+                    ],
+                    "app_paths_of_dependencies": {},
+                    "apps": ["black", "blackd"],
+                    "apps_of_dependencies": [],
+                    "include_apps": true,
+                    "include_dependencies": false,
+                    "man_pages": [],
+                    "man_pages_of_dependencies": [],
+                    "man_paths_of_dependencies": {},
+                    "package": "black",
+                    "package_or_url": "black",
+                    "package_version": "24.8.0",
+                    "pinned": false,
+                    "pip_args": [],
+                    "suffix": ""
+                },
+                "pipx_metadata_version": "0.5",
+                "python_version": "Python 3.12.3",
+                "source_interpreter": {
+                    "__Path__": "/usr/bin/python3",
+                    "__type__": "Path"
+                },
+                "venv_args": []
+            }
+        },
+        "sphinx": {
+            "metadata": {
+                "injected_packages": {},
+                "main_package": {
+                    "app_paths": [],
+                    "app_paths_of_dependencies": {},
+                    "apps": ["sphinx-build"],
+                    "apps_of_dependencies": [],
+                    "include_apps": true,
+                    "include_dependencies": false,
+                    "man_pages": [],
+                    "man_pages_of_dependencies": [],
+                    "man_paths_of_dependencies": {},
+                    "package": "Sphinx",
+                    "package_or_url": "Sphinx",
+                    "package_version": "8.1",
+                    "pinned": false,
+                    "pip_args": [],
+                    "suffix": ""
+                },
+                "pipx_metadata_version": "0.5",
+                "python_version": "Python 3.12.3",
+                "source_interpreter": {
+                    "__Path__": "/usr/bin/python3",
+                    "__type__": "Path"
+                },
+                "venv_args": []
+            }
+        }
+    }
+}"#;
+
+static UV_TOOL_LIST: &str = "black v24.8.0
+- black
+- blackd
+ruff v0.6.9
+- ruff
+typing_extensions v4.12.2
+";
+
+fn semver(v: &str) -> PackageVersion {
+    PackageVersion::Semver(v.parse().unwrap())
+}
+
+#[test]
+fn npm_and_go_status() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_home_file(
+        ".local/lib/node_modules/prettier/package.json",
+        PRETTIER_PACKAGE_JSON,
+    );
+    fs.write_cache_file("logix/npm/prettier/versions.json", PRETTIER_VERSIONS);
+    fs.write_cache_file(
+        "logix/go-proxy/golang.org/x/tools/gopls/list.json",
+        GOPLS_VERSIONS,
+    );
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    let prettier = logix.find_package("prettier").unwrap();
+    let status = prettier.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, semver("3.3.2"));
+    assert_eq!(status.latest_version, semver("3.3.3"));
+    assert_eq!(status.available_version, semver("4.0.0"));
+    assert!(status.need_update());
+    assert!(state.is_installed(&prettier.installed_kind(&state).unwrap()));
+
+    // The binary isn't installed so `go version -m` is never run
+    let gopls = logix.find_package("gopls").unwrap();
+    let status = gopls.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, PackageVersion::None);
+    assert_eq!(status.latest_version, semver("0.16.2"));
+    assert_eq!(
+        gopls.installed_kind(&state).unwrap(),
+        InstalledKind::Go {
+            bin: fs.home_path("go/bin/gopls").to_path_buf()
+        }
+    );
+
+    let black = logix.find_package("black").unwrap();
+    assert_eq!(
+        black.installed_kind(&state).unwrap(),
+        InstalledKind::UvTool {
+            package_name: "Black".into()
+        }
+    );
+}
+
+#[test]
+fn parse_go_build_info() {
+    assert_eq!(
+        GoState::parse_build_info(GO_VERSION_M),
+        Some(("golang.org/x/tools/gopls", semver("0.16.2")))
+    );
+    assert_eq!(
+        GoState::parse_build_info(GO_VERSION_M_DEVEL),
+        Some(("github.com/zeldor/tool/cmd/tool", PackageVersion::None))
+    );
+    assert_eq!(
+        GoState::parse_build_info("/home/zeldor/go/bin/tool: could not read Go build info"),
+        None
+    );
+}
+
+#[test]
+fn parse_python_tools() {
+    let tools = PythonState::parse_pipx_list(PIPX_LIST_JSON).unwrap();
+    assert_eq!(tools.len(), 2);
+    assert_eq!(tools["black"], "24.8.0".parse().unwrap());
+    assert_eq!(tools["sphinx"], "8.1.0".parse().unwrap());
+    assert!(PythonState::parse_pipx_list("Not JSON").is_err());
+
+    let tools = PythonState::parse_uv_list(UV_TOOL_LIST);
+    assert_eq!(tools.len(), 3);
+    assert_eq!(tools["black"], "24.8.0".parse().unwrap());
+    assert_eq!(tools["ruff"], "0.6.9".parse().unwrap());
+    assert_eq!(tools["typing-extensions"], "4.12.2".parse().unwrap());
+}
@@ -0,0 +1,75 @@
+use logix::managed_package::{LatestVersion, PackageVersion};
+
+fn versions(list: &[&str]) -> Vec<semver::Version> {
+    list.iter()
+        .map(|v| semver::Version::parse(v).unwrap())
+        .collect()
+}
+
+fn semver(v: &str) -> PackageVersion {
+    PackageVersion::Semver(semver::Version::parse(v).unwrap())
+}
+
+#[test]
+fn select_with_requirement() {
+    let published = versions(&[
+        "0.9.0",
+        "0.10.1",
+        "0.11.2",
+        "0.11.3-rc.1",
+        "0.12.0",
+        "1.0.0",
+    ]);
+
+    let req = semver::VersionReq::parse(">=0.10, <0.12").unwrap();
+    assert_eq!(
+        LatestVersion::select(Some(&req), published.clone()),
+        LatestVersion {
+            allowed: semver("0.11.2"),
+            available: semver("1.0.0"),
+        }
+    );
+
+    let req = semver::VersionReq::parse("=0.10.1").unwrap();
+    assert_eq!(
+        LatestVersion::select(Some(&req), published.clone()),
+        LatestVersion {
+            allowed: semver("0.10.1"),
+            available: semver("1.0.0"),
+        }
+    );
+
+    let req = semver::VersionReq::parse("^2").unwrap();
+    assert_eq!(
+        LatestVersion::select(Some(&req), published.clone()),
+        LatestVersion {
+            allowed: PackageVersion::None,
+            available: semver("1.0.0"),
+        }
+    );
+
+    assert_eq!(
+        LatestVersion::select(None, published),
+        LatestVersion::unconstrained(semver("1.0.0"))
+    );
+}
+
+#[test]
+fn select_newest_pre_release() {
+    let published = versions(&["5.5.4", "5.6.2", "5.7.0-dev.20241018", "5.7.0-beta"]);
+
+    assert_eq!(
+        LatestVersion::select(None, published.clone()),
+        LatestVersion::unconstrained(semver("5.6.2"))
+    );
+
+    // Pre-releases are only allowed if the requirement asks for them
+    let req = semver::VersionReq::parse(">=5.7.0-beta").unwrap();
+    assert_eq!(
+        LatestVersion::select(Some(&req), published),
+        LatestVersion {
+            allowed: semver("5.7.0-dev.20241018"),
+            available: semver("5.6.2"),
+        }
+    );
+}
@@ -0,0 +1,190 @@
+use logix::{
+    managed_package::{PackageVersion, UpdateStatus},
+    system_state::SystemState,
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      ripgrep: RustCrate {
+        crate_name: "ripgrep"
+      }
+      helix: RustCrate {
+        crate_name: "helix-term"
+        source: GithubBranch {
+          owner: "helix-editor"
+          repo: "helix"
+          branch: "master"
+        }
+      }
+    }
+  }
+}
+"#;
+
+static LOGIX_LOCK: &str = r#"{
+  "version": 1,
+  "packages": {
+    "helix": {
+      "Commit": {
+        "id": "0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e",
+        "date": "2024-09-28T15:37:35Z"
+      }
+    },
+    "ripgrep": {
+      "Semver": "14.1.0"
+    }
+  }
+}
+"#;
+
+fn commit(id: &str) -> PackageVersion {
+    PackageVersion::Commit {
+        id: id.trim().into(),
+        date: None,
+    }
+}
+
+#[test]
+fn load_and_save() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+
+    // A missing lockfile means nothing is locked
+    let logix = fs.load_logix();
+    assert_eq!(logix.lockfile().iter().count(), 0);
+
+    fs.write_config_file("logix/logix.lock", LOGIX_LOCK);
+    let logix = fs.load_logix();
+    let lockfile = logix.lockfile();
+
+    assert_eq!(
+        lockfile.get("ripgrep"),
+        Some(&PackageVersion::Semver("14.1.0".parse().unwrap()))
+    );
+    assert_eq!(
+        lockfile.get("helix"),
+        Some(&PackageVersion::Commit {
+            id: "0ee5850016ae0f2e5de7bfb4ba2ff4b3bc3a2c6e".into(),
+            date: Some("2024-09-28T15:37:35Z".parse().unwrap()),
+        })
+    );
+
+    // Saving an unchanged lockfile should give back the same file
+    let path = logix.lockfile_path().unwrap();
+    lockfile.save(&path).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), LOGIX_LOCK);
+
+    let mut lockfile = lockfile.clone();
+    lockfile.retain(|name| name != "helix");
+    lockfile.save(&path).unwrap();
+
+    let logix = fs.load_logix();
+    assert_eq!(
+        logix.lockfile().iter().collect::<Vec<_>>(),
+        [(
+            "ripgrep",
+            &PackageVersion::Semver("14.1.0".parse().unwrap())
+        )]
+    );
+}
+
+static GIT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      tool: Custom {
+        source: Git {
+          url: "URL"
+        }
+        local_dir: "src/tool"
+        build: ShellCommand {
+          command: "git log -1 --format=%s > built.txt"
+        }
+        install: ShellCommand {
+          command: "cp built.txt ../installed.txt"
+        }
+      }
+    }
+  }
+}
+"#;
+
+#[test]
+fn install_locked_version() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let remote = fs.home_path("remote/tool");
+    std::fs::create_dir_all(&remote).unwrap();
+    helper::git(&remote, &["init", "--quiet", "--initial-branch=main"]);
+    helper::git(
+        &remote,
+        &["commit", "--quiet", "--allow-empty", "-m", "First"],
+    );
+    let first = helper::git(&remote, &["rev-parse", "HEAD"]);
+    helper::git(
+        &remote,
+        &["commit", "--quiet", "--allow-empty", "-m", "Second"],
+    );
+    let second = helper::git(&remote, &["rev-parse", "HEAD"]);
+    fs.write_config_file(
+        "logix/root.logix",
+        &GIT_LOGIX.replace("URL", &helper::file_url(&remote)),
+    );
+    fs.write_config_file(
+        "logix/logix.lock",
+        &format!(
+            r#"{{ "version": 1, "packages": {{ "tool": {{ "Commit": {{ "id": "{}" }} }} }} }}"#,
+            first.trim()
+        ),
+    );
+
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    let package = logix.find_package("tool").unwrap();
+
+    // The locked commit is installed even though the branch has moved on
+    let status = package.calculate_status(&state).unwrap();
+    assert!(status.latest_version.is_same(&commit(&second)));
+    assert_eq!(status.target_version(), &commit(&first));
+    assert!(status.need_update());
+    let version = package
+        .install_update(&mut state, status.target_version())
+        .unwrap();
+    assert!(version.is_same(&commit(&first)));
+    assert_eq!(
+        std::fs::read_to_string(fs.home_path("src/installed.txt")).unwrap(),
+        "First\n"
+    );
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.update_status, UpdateStatus::UpToDate);
+    assert!(!status.need_update());
+
+    // A lock from before the source changed is resolved again instead of installed
+    fs.write_config_file(
+        "logix/logix.lock",
+        r#"{ "version": 1, "packages": { "tool": { "Semver": "1.0.0" } } }"#,
+    );
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    let package = logix.find_package("tool").unwrap();
+
+    let status = package.calculate_status(&state).unwrap();
+    assert_eq!(status.locked_version, PackageVersion::None);
+    assert!(status.target_version().is_same(&commit(&second)));
+    package
+        .install_update(&mut state, status.target_version())
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(fs.home_path("src/installed.txt")).unwrap(),
+        "Second\n"
+    );
+    assert!(!package.calculate_status(&state).unwrap().need_update());
+}
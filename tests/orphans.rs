@@ -0,0 +1,193 @@
+use logix::{
+    error::Error,
+    installed_packages::{InstalledKind, InstalledPackage},
+    managed_package::PackageVersion,
+    system_state::SystemState,
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      ripgrep: RustCrate {
+        crate_name: "ripgrep"
+      }
+      helix: RustCrate {
+        crate_name: "helix-term"
+      }
+    }
+  }
+}
+"#;
+
+static CRATES2_JSON: &str = r#"{
+  "installs": {
+    "ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)": {
+      "bins": ["rg"],
+      "profile": "release"
+    },
+    "bat 0.24.0 (registry+https://github.com/rust-lang/crates.io-index)": {
+      "bins": ["bat"],
+      "profile": "release"
+    },
+    "helix 24.7.0 (registry+https://github.com/rust-lang/crates.io-index)": {
+      "bins": ["hx"],
+      "profile": "release"
+    }
+  }
+}"#;
+
+static INSTALLED_JSON: &str = r#"{
+  "version": 1,
+  "packages": [
+    { "name": "ripgrep", "kind": { "RustCrate": { "crate_name": "ripgrep" } }, "version": { "Semver": "14.1.0" } },
+    { "name": "bat", "kind": { "RustCrate": { "crate_name": "bat" } }, "version": { "Semver": "0.24.0" } },
+    { "name": "helix", "kind": { "RustCrate": { "crate_name": "helix" } }, "version": { "Semver": "24.7.0" } },
+    { "name": "fd", "kind": { "RustCrate": { "crate_name": "fd-find" } }, "version": { "Semver": "10.2.0" } }
+  ]
+}"#;
+
+fn installed(name: &str, crate_name: &str, version: &str) -> InstalledPackage {
+    InstalledPackage {
+        name: name.into(),
+        kind: InstalledKind::RustCrate {
+            crate_name: crate_name.into(),
+        },
+        version: PackageVersion::Semver(version.parse().unwrap()),
+    }
+}
+
+#[test]
+fn find_orphans() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_home_file(".cargo/.crates2.json", CRATES2_JSON);
+    fs.write_home_file(".local/state/logix/installed.json", INSTALLED_JSON);
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    // Removed packages and packages that changed crate are orphans
+    let orphans = logix.find_orphans(&state).unwrap();
+    assert_eq!(
+        orphans,
+        [
+            &installed("bat", "bat", "0.24.0"),
+            &installed("helix", "helix", "24.7.0"),
+            &installed("fd", "fd-find", "10.2.0"),
+        ]
+    );
+
+    // Only orphans that are still installed need to be uninstalled
+    assert!(state.is_installed(&orphans[0].kind));
+    assert!(state.is_installed(&orphans[1].kind));
+    assert!(!state.is_installed(&orphans[2].kind));
+
+    let fd = orphans[2].kind.clone();
+
+    // Recording a package replaces the previous record of the same kind
+    let mut state = state;
+    let updated = installed("ripgrep", "ripgrep", "14.1.1");
+    state.record_installed(updated.clone()).unwrap();
+    assert_eq!(
+        state
+            .installed_packages()
+            .filter(|v| v.name == "ripgrep")
+            .collect::<Vec<_>>(),
+        [&updated]
+    );
+
+    // Forgetting a package that is no longer installed doesn't run cargo
+    state.uninstall(&fd).unwrap();
+
+    let state = SystemState::init(logix.env()).unwrap();
+    assert_eq!(state.installed_packages().count(), 3);
+    assert_eq!(logix.find_orphans(&state).unwrap().len(), 2);
+}
+
+static RENAMED_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      rg: RustCrate {
+        crate_name: "ripgrep"
+      }
+    }
+  }
+}
+"#;
+
+#[test]
+fn renamed_package_is_not_orphan() {
+    let fs = helper::TestFs::new(RENAMED_LOGIX);
+    fs.write_home_file(".cargo/.crates2.json", CRATES2_JSON);
+    fs.write_home_file(".local/state/logix/installed.json", INSTALLED_JSON);
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    // The record still has the old name, but the crate is declared as `rg`,
+    // so pruning before installing must not uninstall it
+    let orphans = logix.find_orphans(&state).unwrap();
+    assert!(
+        !orphans.contains(&&installed("ripgrep", "ripgrep", "14.1.0")),
+        "{orphans:?}"
+    );
+    assert_eq!(
+        orphans,
+        [
+            &installed("bat", "bat", "0.24.0"),
+            &installed("helix", "helix", "24.7.0"),
+            &installed("fd", "fd-find", "10.2.0"),
+        ]
+    );
+}
+
+static BROKEN_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      ripgrep: GitHubRelease {
+        owner: "BurntSushi"
+        repo: "ripgrep"
+        asset_pattern: "ripgrep-(.*"
+        bin: "rg"
+      }
+    }
+  }
+}
+"#;
+
+#[test]
+fn no_orphans_when_a_package_fails() {
+    let fs = helper::TestFs::new(BROKEN_LOGIX);
+    fs.write_home_file(
+        ".local/state/logix/installed.json",
+        &format!(
+            r#"{{
+  "version": 1,
+  "packages": [
+    {{ "name": "ripgrep", "kind": {{ "GitHubRelease": {{ "bin": "{}" }} }}, "version": {{ "Semver": "14.1.0" }} }}
+  ]
+}}"#,
+            fs.home_path(".local/bin/rg").display()
+        ),
+    );
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    // How ripgrep is installed is unknown, so its record must not be pruned
+    let err = logix.find_orphans(&state).unwrap_err();
+    assert!(matches!(err, Error::InvalidAssetPattern(..)), "{err}");
+}
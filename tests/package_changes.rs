@@ -0,0 +1,161 @@
+use logix::{
+    managed_package::{ChangedCommit, ChangedRelease, PackageChanges},
+    system_state::SystemState,
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      tool: Custom {
+        source: GitHub {
+          owner: "zeldor"
+          repo: "tool"
+        }
+        local_dir: "src/tool"
+      }
+      ripgrep: GitHubRelease {
+        owner: "BurntSushi"
+        repo: "ripgrep"
+        bin: "rg"
+      }
+    }
+  }
+}
+"#;
+
+static INSTALLED_JSON: &str = r#"{
+  "version": 1,
+  "packages": [
+    { "name": "tool", "kind": { "Custom": { "local_dir": "TOOL_DIR" } }, "version": { "Commit": { "id": "1111111111111111111111111111111111111111" } } },
+    { "name": "ripgrep", "kind": { "GitHubRelease": { "bin": "RG_BIN" } }, "version": { "Semver": "13.0.0" } }
+  ]
+}"#;
+
+static TOOL_INFO: &str = r#"{ "default_branch": "main" }"#;
+
+static TOOL_BRANCH: &str = r#"{
+  "commit": {
+    "sha": "3333333333333333333333333333333333333333",
+    "commit": { "author": { "name": "Zeldon Kingly", "date": "2024-10-02T08:00:00Z" }, "message": "Fix the frobnicator" }
+  }
+}"#;
+
+static TOOL_COMPARE: &str = r#"{
+  "commits": [
+    {
+      "sha": "2222222222222222222222222222222222222222",
+      "commit": { "author": { "name": "Zeldon Kingly", "date": "2024-10-01T08:00:00Z" }, "message": "Add a frobnicator\n\nIt frobs" }
+    },
+    {
+      "sha": "3333333333333333333333333333333333333333",
+      "commit": { "author": { "name": "Zeldon Kingly", "date": "2024-10-02T08:00:00Z" }, "message": "Fix the frobnicator" }
+    }
+  ]
+}"#;
+
+static RIPGREP_RELEASES: &str = r#"[
+  { "tag_name": "14.1.1", "body": "Fixes a bug", "draft": false, "prerelease": false, "assets": [] },
+  { "tag_name": "14.1.1-rc.1", "body": "Almost fixes a bug", "draft": false, "prerelease": true, "assets": [] },
+  { "tag_name": "14.1.0", "body": "Adds a feature", "draft": false, "prerelease": false, "assets": [] },
+  { "tag_name": "14.1.0-beta.1", "body": "Tries a feature", "draft": false, "prerelease": false, "assets": [] },
+  { "tag_name": "13.0.0", "body": "Old news", "draft": false, "prerelease": false, "assets": [] }
+]"#;
+
+fn write_state(fs: &helper::TestFs) {
+    fs.write_home_file(
+        ".local/state/logix/installed.json",
+        &INSTALLED_JSON
+            .replace("TOOL_DIR", fs.home_path("src/tool").to_str().unwrap())
+            .replace("RG_BIN", fs.home_path(".local/bin/rg").to_str().unwrap()),
+    );
+    fs.write_home_file(".local/bin/rg", "#!/bin/sh\n");
+    fs.write_cache_file("logix/github-repo/zeldor/tool/info.json", TOOL_INFO);
+    fs.write_cache_file(
+        "logix/github-repo/zeldor/tool/branches/main.json",
+        TOOL_BRANCH,
+    );
+    fs.write_cache_file(
+        "logix/github-repo/zeldor/tool/compare/1111111111111111111111111111111111111111/3333333333333333333333333333333333333333.json",
+        TOOL_COMPARE,
+    );
+    fs.write_cache_file(
+        "logix/github-repo/BurntSushi/ripgrep/releases.json",
+        RIPGREP_RELEASES,
+    );
+}
+
+fn release_notes(fs: &helper::TestFs) -> Vec<(String, String)> {
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+    let ripgrep = logix.find_package("ripgrep").unwrap();
+    let status = ripgrep.calculate_status(&state).unwrap();
+    assert!(status.need_update());
+    let Some(PackageChanges::Releases(releases)) = ripgrep.changes(&state, &status).unwrap() else {
+        panic!("expected releases");
+    };
+    releases
+        .into_iter()
+        .map(|ChangedRelease { tag, notes, .. }| (tag, notes))
+        .collect()
+}
+
+#[test]
+fn commits_and_releases() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    write_state(&fs);
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    let tool = logix.find_package("tool").unwrap();
+    let status = tool.calculate_status(&state).unwrap();
+    assert!(status.need_update());
+    let Some(PackageChanges::Commits(commits)) = tool.changes(&state, &status).unwrap() else {
+        panic!("expected commits");
+    };
+    assert_eq!(
+        commits
+            .iter()
+            .map(|ChangedCommit { id, summary, .. }| (&id[..4], summary.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            ("2222", "Add a frobnicator"),
+            ("3333", "Fix the frobnicator")
+        ]
+    );
+
+    // Pre-releases are left out by default
+    assert_eq!(
+        release_notes(&fs),
+        [
+            ("14.1.1".into(), "Fixes a bug".into()),
+            ("14.1.0".into(), "Adds a feature".into())
+        ]
+    );
+}
+
+#[test]
+fn allowed_pre_releases() {
+    let fs = helper::TestFs::new(&ROOT_LOGIX.replace(
+        "bin: \"rg\"",
+        "bin: \"rg\"\n        version: \">=14.1.0-beta.1\"",
+    ));
+    write_state(&fs);
+
+    // Pre-release versions the requirement allows are included, but not releases
+    // marked as a pre-release on GitHub as those are never installed
+    assert_eq!(
+        release_notes(&fs),
+        [
+            ("14.1.1".into(), "Fixes a bug".into()),
+            ("14.1.0".into(), "Adds a feature".into()),
+            ("14.1.0-beta.1".into(), "Tries a feature".into())
+        ]
+    );
+}
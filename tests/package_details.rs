@@ -0,0 +1,122 @@
+use logix::{managed_file::FileStatus, managed_package::PackageDetails, system_state::SystemState};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      ripgrep: GitHubRelease {
+        owner: "BurntSushi"
+        repo: "ripgrep"
+        bin: "rg"
+        config_dir: User {
+          priority: 0
+        }
+      }
+      helix: Custom {
+        source: GitHub {
+          owner: "helix-editor"
+          repo: "helix"
+        }
+        local_dir: "src/helix"
+        config_dir: User {
+          priority: 0
+        }
+      }
+      tool: RustCrate {
+        source: GithubBranch {
+          owner: "zeldor"
+          repo: "tool"
+          branch: "next"
+        }
+        environment: {
+          RUSTFLAGS: "-C target-cpu=native"
+        }
+      }
+    }
+  }
+}
+"#;
+
+#[test]
+fn package_details() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    let details = logix
+        .find_package("ripgrep")
+        .unwrap()
+        .details(&state)
+        .unwrap();
+    assert_eq!(
+        details,
+        PackageDetails {
+            source: Some("https://github.com/BurntSushi/ripgrep releases".into()),
+            location: Some(fs.home_path(".local/bin").to_path_buf()),
+            bins: vec![fs.home_path(".local/bin/rg").to_path_buf()],
+            environment: Vec::new(),
+        }
+    );
+
+    let details = logix
+        .find_package("helix")
+        .unwrap()
+        .details(&state)
+        .unwrap();
+    assert_eq!(
+        details.source.as_deref(),
+        Some("https://github.com/helix-editor/helix.git")
+    );
+    assert_eq!(
+        details.location,
+        Some(fs.home_path("src/helix").to_path_buf())
+    );
+
+    let details = logix.find_package("tool").unwrap().details(&state).unwrap();
+    assert_eq!(
+        details.source.as_deref(),
+        Some("https://github.com/zeldor/tool.git branch next")
+    );
+    assert_eq!(
+        details.environment,
+        [("RUSTFLAGS".to_string(), "-C target-cpu=native".to_string())]
+    );
+}
+
+#[test]
+fn package_config_status() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_config_file("ripgrep/config", "--smart-case");
+    fs.write_config_file("logix/config/ripgrep/config", "--smart-case");
+    fs.write_config_file("helix/config.toml", "# Dummy config");
+    let logix = fs.load_logix();
+
+    let files = logix
+        .calculate_package_config_status("ripgrep")
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        files,
+        [(
+            FileStatus::UpToDate,
+            fs.managed_logix_config("ripgrep", "ripgrep/config")
+        )]
+    );
+
+    let files = logix
+        .calculate_package_config_status("helix")
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        files,
+        [(
+            FileStatus::LocalAdded,
+            fs.managed_logix_config("helix", "helix/config.toml")
+        )]
+    );
+}
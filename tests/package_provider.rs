@@ -0,0 +1,278 @@
+use logix::{
+    config::Package,
+    error::Error,
+    installed_packages::{InstalledKind, InstalledPackage},
+    managed_package::{LatestVersion, ManagedPackage, PackageVersion},
+    package_provider::{PackageProvider, ProviderContext},
+    system_state::SystemState,
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      prettier: NpmPackage {
+      }
+    }
+  }
+}
+"#;
+
+fn semver(v: &str) -> PackageVersion {
+    PackageVersion::Semver(v.parse().unwrap())
+}
+
+/// Pretends to install node packages using a vendored copy
+struct VendoredNpm {
+    installed: Vec<String>,
+}
+
+impl PackageProvider for VendoredNpm {
+    fn handles(&self, package: &Package) -> bool {
+        matches!(package, Package::NpmPackage { .. })
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::Npm { .. })
+    }
+
+    fn installed_version(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        Ok(if self.installed.iter().any(|v| v == package.name()) {
+            semver("3.0.0")
+        } else {
+            PackageVersion::None
+        })
+    }
+
+    fn latest_version(
+        &self,
+        _ctx: &ProviderContext,
+        _package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        Ok(LatestVersion::unconstrained(semver("3.0.0")))
+    }
+
+    fn install(
+        &mut self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        self.installed.push(package.name().into());
+        Ok(version.clone())
+    }
+
+    fn installed_kind(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        Ok(InstalledKind::Npm {
+            package_name: package.name().into(),
+        })
+    }
+
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        match kind {
+            InstalledKind::Npm { package_name } => self.installed.contains(package_name),
+            _ => false,
+        }
+    }
+
+    fn uninstall(&mut self, _ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        self.installed
+            .retain(|v| !matches!(kind, InstalledKind::Npm { package_name } if package_name == v));
+        Ok(())
+    }
+}
+
+#[test]
+fn registered_provider_replaces_builtin() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    state.register_provider(VendoredNpm {
+        installed: Vec::new(),
+    });
+
+    let prettier = logix.find_package("prettier").unwrap();
+    let status = prettier.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, PackageVersion::None);
+    assert!(status.need_update());
+
+    let installed = prettier
+        .install_update(&mut state, status.target_version())
+        .unwrap();
+    assert_eq!(installed, semver("3.0.0"));
+    assert_eq!(
+        state.provider::<VendoredNpm>().unwrap().installed,
+        ["prettier"]
+    );
+
+    let record = InstalledPackage {
+        name: "prettier".into(),
+        kind: InstalledKind::Npm {
+            package_name: "prettier".into(),
+        },
+        version: semver("3.0.0"),
+    };
+    assert_eq!(state.installed_packages().collect::<Vec<_>>(), [&record]);
+    assert!(state.list_installed().unwrap().contains(&record));
+
+    state.uninstall(&record.kind).unwrap();
+    assert!(state
+        .provider::<VendoredNpm>()
+        .unwrap()
+        .installed
+        .is_empty());
+    assert_eq!(state.installed_packages().count(), 0);
+}
+
+static EXTENSION_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      firefox: Extension {
+        provider: "flatpak"
+        args: {
+          app_id: "org.mozilla.firefox"
+        }
+      }
+    }
+  }
+}
+"#;
+
+/// Pretends to install flatpak apps, a kind of package logix doesn't know about
+#[derive(Default)]
+struct Flatpak {
+    installed: Vec<String>,
+}
+
+impl Flatpak {
+    fn app_id(package: &ManagedPackage) -> Result<String, Error> {
+        match package.package() {
+            Package::Extension { args, .. } => args
+                .iter()
+                .find_map(|(key, value)| (&**key == "app_id").then(|| value.clone()))
+                .ok_or_else(|| Error::UnsupportedPackage(package.name().into(), "flatpak")),
+            _ => Err(Error::UnsupportedPackage(package.name().into(), "flatpak")),
+        }
+    }
+}
+
+impl PackageProvider for Flatpak {
+    fn handles(&self, package: &Package) -> bool {
+        package.is_extension("flatpak")
+    }
+
+    fn handles_installed(&self, kind: &InstalledKind) -> bool {
+        matches!(kind, InstalledKind::Extension { provider, .. } if provider == "flatpak")
+    }
+
+    fn installed_version(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<PackageVersion, Error> {
+        Ok(if self.installed.contains(&Self::app_id(package)?) {
+            PackageVersion::Other("131.0".into())
+        } else {
+            PackageVersion::None
+        })
+    }
+
+    fn latest_version(
+        &self,
+        _ctx: &ProviderContext,
+        _package: &ManagedPackage,
+    ) -> Result<LatestVersion, Error> {
+        Ok(LatestVersion::unconstrained(PackageVersion::Other(
+            "131.0".into(),
+        )))
+    }
+
+    fn install(
+        &mut self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+        version: &PackageVersion,
+    ) -> Result<PackageVersion, Error> {
+        self.installed.push(Self::app_id(package)?);
+        Ok(version.clone())
+    }
+
+    fn installed_kind(
+        &self,
+        _ctx: &ProviderContext,
+        package: &ManagedPackage,
+    ) -> Result<InstalledKind, Error> {
+        Ok(InstalledKind::Extension {
+            provider: "flatpak".into(),
+            package_name: Self::app_id(package)?,
+        })
+    }
+
+    fn is_installed(&self, _ctx: &ProviderContext, kind: &InstalledKind) -> bool {
+        match kind {
+            InstalledKind::Extension { package_name, .. } => self.installed.contains(package_name),
+            _ => false,
+        }
+    }
+
+    fn uninstall(&mut self, _ctx: &ProviderContext, kind: &InstalledKind) -> Result<(), Error> {
+        if let InstalledKind::Extension { package_name, .. } = kind {
+            self.installed.retain(|v| v != package_name);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn registered_provider_adds_package_kind() {
+    let fs = helper::TestFs::new(EXTENSION_LOGIX);
+
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    let firefox = logix.find_package("firefox").unwrap();
+
+    // No built-in provider handles extensions
+    let err = firefox.calculate_status(&state).unwrap_err();
+    assert!(matches!(err, Error::NoPackageProvider(_)), "{err}");
+
+    state.register_provider(Flatpak::default());
+    let status = firefox.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, PackageVersion::None);
+    assert!(status.need_update());
+
+    firefox
+        .install_update(&mut state, status.target_version())
+        .unwrap();
+    assert_eq!(
+        state.provider::<Flatpak>().unwrap().installed,
+        ["org.mozilla.firefox"]
+    );
+    assert!(!firefox.calculate_status(&state).unwrap().need_update());
+
+    let kind = InstalledKind::Extension {
+        provider: "flatpak".into(),
+        package_name: "org.mozilla.firefox".into(),
+    };
+    assert_eq!(logix.find_orphans(&state).unwrap().len(), 0);
+    state.uninstall(&kind).unwrap();
+    assert!(state.provider::<Flatpak>().unwrap().installed.is_empty());
+    assert_eq!(state.installed_packages().count(), 0);
+}
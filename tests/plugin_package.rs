@@ -0,0 +1,186 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+
+use logix::{
+    installed_packages::InstalledKind, managed_package::PackageVersion, system_state::SystemState,
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      tool: Plugin {
+        provider: "{stub}"
+        args: {
+          state_dir: "{state_dir}"
+        }
+      }
+      future: Plugin {
+        provider: "{future}"
+      }
+    }
+  }
+}
+"#;
+
+/// Keeps the installed version in a file in the `state_dir` argument
+static STUB_PROVIDER: &str = r#"#!/bin/sh
+read -r request
+state_dir=$(printf '%s' "$request" | sed -n 's/.*"state_dir":"\([^"]*\)".*/\1/p')
+# NOTE: The arguments are not sent when uninstalling
+[ -n "$state_dir" ] || state_dir="$(dirname "$0")/../.local/state/stub"
+case "$request" in
+  *'"protocol":1,'*) ;;
+  *) echo '{"protocol":1,"error":"unsupported protocol"}'; exit 0 ;;
+esac
+case "$request" in
+  *'"operation":"status"'*)
+    if [ -f "$state_dir/installed" ]; then
+      printf '{"protocol":1,"installed":{"type":"semver","version":"%s"}}\n' "$(cat "$state_dir/installed")"
+    else
+      echo '{"protocol":1,"installed":null}'
+    fi
+    ;;
+  *'"operation":"latest"'*)
+    echo '{"protocol":1,"latest":{"type":"semver","version":"1.2.0"},"available":{"type":"semver","version":"2.0.0"}}'
+    ;;
+  *'"operation":"install"'*)
+    version=$(printf '%s' "$request" | sed -n 's/.*"version":{"type":"semver","version":"\([^"]*\)"}.*/\1/p')
+    mkdir -p "$state_dir"
+    printf '%s' "$version" > "$state_dir/installed"
+    printf '{"protocol":1,"installed":{"type":"semver","version":"%s"}}\n' "$version"
+    ;;
+  *'"operation":"uninstall"'*)
+    rm -f "$state_dir/installed"
+    echo '{"protocol":1}'
+    ;;
+  *) echo '{"protocol":1,"error":"unknown operation"}' ;;
+esac
+"#;
+
+/// A provider speaking a newer version of the protocol
+static FUTURE_PROVIDER: &str = r#"#!/bin/sh
+cat > /dev/null
+echo '{"protocol":2}'
+"#;
+
+fn semver(v: &str) -> PackageVersion {
+    PackageVersion::Semver(v.parse().unwrap())
+}
+
+fn write_executable(fs: &helper::TestFs, path: &str, data: &str) -> String {
+    fs.write_home_file(path, data);
+    let path = fs.home_path(path);
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path.display().to_string()
+}
+
+#[test]
+fn plugin_protocol() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let stub = write_executable(&fs, "bin/logix-provider-stub", STUB_PROVIDER);
+    let future = write_executable(&fs, "bin/logix-provider-future", FUTURE_PROVIDER);
+    let state_dir = fs.home_path(".local/state/stub").display().to_string();
+    fs.write_config_file(
+        "logix/root.logix",
+        &ROOT_LOGIX
+            .replace("{stub}", &stub)
+            .replace("{state_dir}", &state_dir)
+            .replace("{future}", &future),
+    );
+
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+
+    let tool = logix.find_package("tool").unwrap();
+    let status = tool.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, PackageVersion::None);
+    assert_eq!(status.latest_version, semver("1.2.0"));
+    assert_eq!(status.available_version, semver("2.0.0"));
+    assert!(status.need_update());
+
+    let installed = tool
+        .install_update(&mut state, status.target_version())
+        .unwrap();
+    assert_eq!(installed, semver("1.2.0"));
+
+    let status = tool.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, semver("1.2.0"));
+    assert!(!status.need_update());
+
+    let kind = InstalledKind::Plugin {
+        provider: stub,
+        package_name: "tool".into(),
+    };
+    assert_eq!(tool.installed_kind(&state).unwrap(), kind);
+    assert_eq!(
+        state.installed_package("tool", &kind).unwrap().version,
+        semver("1.2.0")
+    );
+
+    let err = logix
+        .find_package("future")
+        .unwrap()
+        .calculate_status(&state)
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("unsupported protocol version 2"),
+        "{err}"
+    );
+}
+
+static EMPTY_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+  }
+}
+"#;
+
+#[test]
+fn plugin_uninstall() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    let stub = write_executable(&fs, "bin/logix-provider-stub", STUB_PROVIDER);
+    let future = write_executable(&fs, "bin/logix-provider-future", FUTURE_PROVIDER);
+    fs.write_home_file(".local/state/stub/installed", "1.2.0");
+    fs.write_home_file(
+        ".local/state/logix/installed.json",
+        &format!(
+            r#"{{
+  "version": 1,
+  "packages": [
+    {{ "name": "tool", "kind": {{ "Plugin": {{ "provider": "{stub}", "package_name": "tool" }} }}, "version": {{ "Semver": "1.2.0" }} }},
+    {{ "name": "future", "kind": {{ "Plugin": {{ "provider": "{future}", "package_name": "future" }} }}, "version": {{ "Semver": "1.0.0" }} }}
+  ]
+}}"#
+        ),
+    );
+    fs.write_config_file("logix/root.logix", EMPTY_LOGIX);
+
+    let logix = fs.load_logix();
+    let mut state = SystemState::init(logix.env()).unwrap();
+    let orphans = logix
+        .find_orphans(&state)
+        .unwrap()
+        .into_iter()
+        .map(|v| v.kind.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(orphans.len(), 2);
+
+    // The provider uninstalls the package before the record is removed
+    state.uninstall(&orphans[0]).unwrap();
+    assert!(!fs.home_path(".local/state/stub/installed").exists());
+    assert!(state.installed_package("tool", &orphans[0]).is_none());
+
+    // The record is kept when the provider fails to uninstall it
+    assert!(state.uninstall(&orphans[1]).is_err());
+    assert!(state.installed_package("future", &orphans[1]).is_some());
+}
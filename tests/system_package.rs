@@ -0,0 +1,194 @@
+use logix::{
+    installed_packages::InstalledKind,
+    managed_package::PackageVersion,
+    system_state::{SystemPackageManager, SystemState},
+};
+
+mod helper;
+
+static APT_CACHE_POLICY: &str = "libssl-dev:
+  Installed: 3.0.13-0ubuntu3.3
+  Candidate: 3.0.13-0ubuntu3.4
+  Version table:
+     3.0.13-0ubuntu3.4 500
+        500 http://archive.ubuntu.com/ubuntu noble-updates/main amd64 Packages
+        500 http://security.ubuntu.com/ubuntu noble-security/main amd64 Packages
+ *** 3.0.13-0ubuntu3.3 100
+        100 /var/lib/dpkg/status
+     3.0.13-0ubuntu3 500
+        500 http://archive.ubuntu.com/ubuntu noble/main amd64 Packages
+";
+
+static APT_CACHE_POLICY_UNAVAILABLE: &str = "fontconfig:
+  Installed: (none)
+  Candidate: (none)
+  Version table:
+";
+
+static PACMAN_SI: &str = "Repository      : core
+Name            : openssl
+Version         : 3.3.2-1
+Description     : The Open Source toolkit for Secure Sockets Layer and Transport Layer Security
+Architecture    : x86_64
+URL             : https://www.openssl.org
+Licenses        : Apache-2.0
+Groups          : None
+Provides        : libcrypto.so=3-64  libssl.so=3-64
+Depends On      : glibc
+Optional Deps   : ca-certificates
+                  perl
+Conflicts With  : None
+Replaces        : None
+Download Size   : 4.93 MiB
+Installed Size  : 13.69 MiB
+Packager        : Pierre Schmitz <pierre@archlinux.de>
+Build Date      : Wed 04 Sep 2024 04:52:50 PM CEST
+Validated By    : MD5 Sum  SHA-256 Sum  Signature
+
+";
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      openssl: System {
+        apt: "libssl-dev"
+        dnf: "openssl-devel"
+      }
+      fontconfig: System {
+        apt: "fontconfig"
+        mode: Install
+      }
+    }
+  }
+}
+"#;
+
+static ARCH_OS_RELEASE: &str = r#"NAME="Arch Linux"
+PRETTY_NAME="Arch Linux"
+ID=arch
+BUILD_ID=rolling
+"#;
+
+static MINT_OS_RELEASE: &str = r#"NAME="Linux Mint"
+VERSION="22 (Wilma)"
+ID=linuxmint
+ID_LIKE="ubuntu debian"
+"#;
+
+#[test]
+fn detect_package_manager() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+    assert_eq!(state.system_package_manager(), None);
+
+    fs.write_os_release(MINT_OS_RELEASE);
+    let state = SystemState::init(logix.env()).unwrap();
+    assert_eq!(
+        state.system_package_manager(),
+        Some(SystemPackageManager::Apt)
+    );
+
+    fs.write_os_release(ARCH_OS_RELEASE);
+    let state = SystemState::init(logix.env()).unwrap();
+    assert_eq!(
+        state.system_package_manager(),
+        Some(SystemPackageManager::Pacman)
+    );
+}
+
+#[test]
+fn skipped_without_name() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_os_release(ARCH_OS_RELEASE);
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    // There is no pacman name, so pacman is never queried
+    let openssl = logix.find_package("openssl").unwrap();
+    let status = openssl.calculate_status(&state).unwrap();
+    assert_eq!(status.installed_version, PackageVersion::None);
+    assert_eq!(status.latest_version, PackageVersion::None);
+    assert!(!status.need_update());
+    assert!(openssl.is_check_only(&state));
+    assert_eq!(
+        openssl.installed_kind(&state).unwrap(),
+        InstalledKind::System {
+            package_name: "openssl".into()
+        }
+    );
+
+    assert!(!logix
+        .find_package("fontconfig")
+        .unwrap()
+        .is_check_only(&state));
+}
+
+fn other(v: &str) -> PackageVersion {
+    PackageVersion::Other(v.into())
+}
+
+#[test]
+fn parse_installed_version() {
+    let apt = SystemPackageManager::Apt;
+    assert_eq!(
+        apt.parse_installed_version("install ok installed\t3.0.13-0ubuntu3.3"),
+        other("3.0.13-0ubuntu3.3")
+    );
+    // Removed but not purged
+    assert_eq!(
+        apt.parse_installed_version("deinstall ok config-files\t3.0.13-0ubuntu3.3"),
+        PackageVersion::None
+    );
+    assert_eq!(
+        apt.parse_installed_version("unknown ok not-installed\t"),
+        PackageVersion::None
+    );
+
+    // Several versions of the same package can be installed
+    let dnf = SystemPackageManager::Dnf;
+    assert_eq!(
+        dnf.parse_installed_version("3.2.2-3.fc40\n"),
+        other("3.2.2-3.fc40")
+    );
+    assert_eq!(
+        dnf.parse_installed_version("6.10.12-200.fc40\n6.11.3-200.fc40\n"),
+        other("6.10.12-200.fc40")
+    );
+
+    let pacman = SystemPackageManager::Pacman;
+    assert_eq!(
+        pacman.parse_installed_version("openssl 3.3.2-1\n"),
+        other("3.3.2-1")
+    );
+}
+
+#[test]
+fn parse_candidate_version() {
+    let apt = SystemPackageManager::Apt;
+    assert_eq!(
+        apt.parse_candidate_version(APT_CACHE_POLICY),
+        other("3.0.13-0ubuntu3.4")
+    );
+    assert_eq!(
+        apt.parse_candidate_version(APT_CACHE_POLICY_UNAVAILABLE),
+        PackageVersion::None
+    );
+    assert_eq!(apt.parse_candidate_version(""), PackageVersion::None);
+
+    let dnf = SystemPackageManager::Dnf;
+    assert_eq!(
+        dnf.parse_candidate_version("3.2.2-3.fc40\n"),
+        other("3.2.2-3.fc40")
+    );
+    assert_eq!(dnf.parse_candidate_version(""), PackageVersion::None);
+
+    let pacman = SystemPackageManager::Pacman;
+    assert_eq!(pacman.parse_candidate_version(PACMAN_SI), other("3.3.2-1"));
+}
@@ -0,0 +1,257 @@
+use logix::{
+    managed_package::{ManagedPackage, PackageStatus, PackageVersion, UpdateStatus, VersionOrder},
+    system_state::SystemState,
+};
+
+mod helper;
+
+static ROOT_LOGIX: &str = r#"
+Logix {
+  home: UserProfile {
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {
+      tool: Custom {
+        source: GitHub {
+          owner: "zeldor"
+          repo: "tool"
+        }
+        local_dir: "src/tool"
+      }
+      ripgrep: GitHubRelease {
+        owner: "BurntSushi"
+        repo: "ripgrep"
+        bin: "rg"
+      }
+    }
+  }
+}
+"#;
+
+static INSTALLED_JSON: &str = r#"{
+  "version": 1,
+  "packages": [
+    { "name": "tool", "kind": { "Custom": { "local_dir": "TOOL_DIR" } }, "version": { "Commit": { "id": "1111111111111111111111111111111111111111" } } },
+    { "name": "ripgrep", "kind": { "GitHubRelease": { "bin": "RG_BIN" } }, "version": { "Semver": "15.0.0" } }
+  ]
+}"#;
+
+static TOOL_INFO: &str = r#"{ "default_branch": "main" }"#;
+
+static TOOL_BRANCH: &str = r#"{
+  "commit": {
+    "sha": "3333333333333333333333333333333333333333",
+    "commit": { "author": { "name": "Zeldon Kingly", "date": "2024-10-02T08:00:00Z" }, "message": "Rewrite history" }
+  }
+}"#;
+
+static TOOL_COMPARE: &str = r#"{ "status": "diverged", "commits": [] }"#;
+
+static RIPGREP_RELEASES: &str = r#"[
+  { "tag_name": "14.1.1", "draft": false, "prerelease": false, "assets": [] },
+  { "tag_name": "14.1.0", "draft": false, "prerelease": false, "assets": [] }
+]"#;
+
+fn semver(v: &str) -> PackageVersion {
+    PackageVersion::Semver(v.parse().unwrap())
+}
+
+fn commit(id: &str, date: Option<&str>) -> PackageVersion {
+    PackageVersion::Commit {
+        id: id.into(),
+        date: date.map(|v| v.parse().unwrap()),
+    }
+}
+
+#[test]
+fn version_order() {
+    let none = PackageVersion::None;
+    assert_eq!(none.compare(&none), VersionOrder::Same);
+    assert_eq!(none.compare(&semver("1.0.0")), VersionOrder::Older);
+    assert_eq!(semver("1.0.0").compare(&none), VersionOrder::Newer);
+
+    assert_eq!(
+        semver("1.2.0").compare(&semver("1.10.0")),
+        VersionOrder::Older
+    );
+    assert_eq!(
+        semver("2.0.0").compare(&semver("1.10.0")),
+        VersionOrder::Newer
+    );
+
+    let old = commit("aaaa", Some("2024-09-01T08:00:00Z"));
+    let new = commit("bbbb", Some("2024-10-01T08:00:00Z"));
+    assert_eq!(old.compare(&new), VersionOrder::Older);
+    assert_eq!(new.compare(&old), VersionOrder::Newer);
+    // NOTE: The date is not known for all sources, and ids may be abbreviated
+    assert_eq!(
+        commit("aaaa1234", None).compare(&commit("aaaa", Some("2024-09-01T08:00:00Z"))),
+        VersionOrder::Same
+    );
+    assert_eq!(
+        commit("aaaa", None).compare(&commit("bbbb", None)),
+        VersionOrder::Unknown
+    );
+
+    let other = PackageVersion::Other("1.2-1ubuntu1".into());
+    assert_eq!(other.compare(&other.clone()), VersionOrder::Same);
+    assert_eq!(
+        other.compare(&PackageVersion::Other("1.2-2".into())),
+        VersionOrder::Unknown
+    );
+    assert_eq!(semver("1.0.0").compare(&new), VersionOrder::Unknown);
+}
+
+#[test]
+fn ahead_and_diverged() {
+    let fs = helper::TestFs::new(ROOT_LOGIX);
+    fs.write_home_file(
+        ".local/state/logix/installed.json",
+        &INSTALLED_JSON
+            .replace("TOOL_DIR", fs.home_path("src/tool").to_str().unwrap())
+            .replace("RG_BIN", fs.home_path(".local/bin/rg").to_str().unwrap()),
+    );
+    fs.write_home_file(".local/bin/rg", "#!/bin/sh\n");
+    fs.write_cache_file("logix/github-repo/zeldor/tool/info.json", TOOL_INFO);
+    fs.write_cache_file(
+        "logix/github-repo/zeldor/tool/branches/main.json",
+        TOOL_BRANCH,
+    );
+    fs.write_cache_file(
+        "logix/github-repo/zeldor/tool/compare/1111111111111111111111111111111111111111/3333333333333333333333333333333333333333.json",
+        TOOL_COMPARE,
+    );
+    fs.write_cache_file(
+        "logix/github-repo/BurntSushi/ripgrep/releases.json",
+        RIPGREP_RELEASES,
+    );
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+
+    // The branch was force pushed, so the remote is installed
+    let status = logix
+        .find_package("tool")
+        .unwrap()
+        .calculate_status(&state)
+        .unwrap();
+    assert_eq!(status.update_status, UpdateStatus::Diverged);
+    assert!(status.need_update());
+
+    // A newer version than the remote is not downgraded unless it is locked
+    let mut status = logix
+        .find_package("ripgrep")
+        .unwrap()
+        .calculate_status(&state)
+        .unwrap();
+    assert_eq!(status.installed_version, semver("15.0.0"));
+    assert_eq!(status.latest_version, semver("14.1.1"));
+    assert_eq!(status.update_status, UpdateStatus::AheadOfRemote);
+    assert!(!status.need_update());
+
+    status.locked_version = semver("14.1.0");
+    assert!(status.need_update());
+}
+
+#[test]
+fn parallel_status_keeps_order() {
+    // Declared out of alphabetical order, each with its own latest release
+    let names = (0..24).map(|i| (i * 7) % 24).collect::<Vec<_>>();
+    let packages = names
+        .iter()
+        .map(|i| {
+            format!(
+                r#"
+      tool-{i}: GitHubRelease {{
+        owner: "zeldor"
+        repo: "tool-{i}"
+        bin: "tool-{i}"
+      }}"#
+            )
+        })
+        .collect::<String>();
+    let fs = helper::TestFs::new(&format!(
+        r#"
+Logix {{
+  home: UserProfile {{
+    username: "zeldor"
+    name: "Zeldon Kingly"
+    email: "zeldor@example.com"
+    packages: {{{packages}
+    }}
+  }}
+}}
+"#
+    ));
+    for i in &names {
+        fs.write_cache_file(
+            &format!("logix/github-repo/zeldor/tool-{i}/releases.json"),
+            &format!(
+                r#"[{{ "tag_name": "1.{i}.0", "draft": false, "prerelease": false, "assets": [] }}]"#
+            ),
+        );
+    }
+
+    let logix = fs.load_logix();
+    let state = SystemState::init(logix.env()).unwrap();
+    let packages = logix.iter_packages().collect::<Vec<_>>();
+    assert_eq!(
+        packages.iter().map(|v| v.name()).collect::<Vec<_>>(),
+        names
+            .iter()
+            .map(|i| format!("tool-{i}"))
+            .collect::<Vec<_>>()
+    );
+
+    let statuses = ManagedPackage::calculate_status_of_all(&state, &packages);
+    assert_eq!(statuses.len(), names.len());
+    for (i, status) in names.iter().zip(statuses) {
+        assert_eq!(status.unwrap().latest_version, semver(&format!("1.{i}.0")));
+    }
+}
+
+fn status(installed: PackageVersion, latest: PackageVersion, order: VersionOrder) -> PackageStatus {
+    PackageStatus {
+        update_status: UpdateStatus::new(&installed, &latest, order),
+        installed_version: installed,
+        downloaded_version: PackageVersion::None,
+        available_version: latest.clone(),
+        latest_version: latest,
+        locked_version: PackageVersion::None,
+        warnings: Vec::new(),
+    }
+}
+
+#[test]
+fn unordered_versions() {
+    let a = "1111111111111111111111111111111111111111";
+    let b = "2222222222222222222222222222222222222222";
+
+    // A comparison that failed is up to date when the ids match
+    let res = status(
+        commit(a, None),
+        commit(&a[..12], None),
+        VersionOrder::Unknown,
+    );
+    assert_eq!(res.update_status, UpdateStatus::UpToDate);
+    assert!(!res.need_update());
+
+    // Other commits of a plain git remote can't be ordered, but they are followed
+    let res = status(commit(a, None), commit(b, None), VersionOrder::Unknown);
+    assert_eq!(res.update_status, UpdateStatus::Changed);
+    assert!(res.need_update());
+
+    let res = status(
+        PackageVersion::Other("1.2-1".into()),
+        PackageVersion::Other("1.2-2".into()),
+        VersionOrder::Unknown,
+    );
+    assert_eq!(res.update_status, UpdateStatus::Changed);
+    assert!(res.need_update());
+
+    // Nothing is reinstalled when the target is unknown
+    let res = status(commit(a, None), PackageVersion::None, VersionOrder::Newer);
+    assert_eq!(res.update_status, UpdateStatus::Unknown);
+    assert!(!res.need_update());
+}